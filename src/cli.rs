@@ -8,10 +8,21 @@ fn cli() -> Command {
         .arg_required_else_help(true)
         .subcommands(vec![
             subcommand_new(),
+            subcommand_apply(),
             subcommand_add(),
+            subcommand_scan(),
+            subcommand_edit(),
             subcommand_list(),
+            subcommand_remove(),
+            subcommand_show(),
+            subcommand_path(),
+            subcommand_init_shell(),
+            subcommand_doctor(),
+            subcommand_export(),
+            subcommand_import(),
             subcommand_reset(),
             subcommand_config(),
+            subcommand_template(),
         ])
 }
 
@@ -52,6 +63,88 @@ fn subcommand_new() -> Command {
             .long("interactive")
             .action(ArgAction::SetTrue),
     ])
+    .args(template_args())
+    .mut_arg("no-input", |arg| arg.conflicts_with("interactive"))
+}
+
+fn subcommand_apply() -> Command {
+    Command::new("apply")
+        .about("Apply templates to an existing Project")
+        .args(&[
+            Arg::new("project")
+                .required(true)
+                .help("The name, id, or directory of the project"),
+            Arg::new("templates")
+                .short('t')
+                .long("template")
+                .required(true)
+                .num_args(1..)
+                .action(ArgAction::Append)
+                .help("Templates to apply to the project"),
+            Arg::new("skip-existing")
+                .long("skip-existing")
+                .help("Keep files that already exist in the project. This is the default")
+                .conflicts_with_all(["overwrite", "prompt"])
+                .action(ArgAction::SetTrue),
+            Arg::new("overwrite")
+                .long("overwrite")
+                .help("Replace files that already exist in the project")
+                .conflicts_with("prompt")
+                .action(ArgAction::SetTrue),
+            Arg::new("prompt")
+                .long("prompt")
+                .help("Ask before replacing each file that already exists in the project")
+                .conflicts_with("no-input")
+                .action(ArgAction::SetTrue),
+        ])
+        .args(template_args())
+}
+
+/// Arguments for rendering templates, shared by every command that builds templates
+fn template_args() -> [Arg; 7] {
+    [
+        Arg::new("verbose")
+            .short('v')
+            .long("verbose")
+            .help("Show where and how each template command runs, along with its output")
+            .conflicts_with("quiet")
+            .action(ArgAction::SetTrue),
+        Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .help("Hide the output of template commands unless one fails")
+            .action(ArgAction::SetTrue),
+        Arg::new("dry-run")
+            .long("dry-run")
+            .help("Show the files that would be written and the commands that would run, without changing anything")
+            .action(ArgAction::SetTrue),
+        Arg::new("diff")
+            .long("diff")
+            .help("With `--dry-run`, show how existing files would change")
+            .requires("dry-run")
+            .action(ArgAction::SetTrue),
+        Arg::new("vars")
+            .long("var")
+            .value_name("KEY=VALUE")
+            .action(ArgAction::Append)
+            .value_parser(parse_key_value)
+            .help("Set the value of a template variable"),
+        Arg::new("vars-file")
+            .long("vars-file")
+            .help("A YAML, JSON, or TOML file with the values of template variables")
+            .value_parser(value_parser!(PathBuf)),
+        Arg::new("no-input")
+            .long("no-input")
+            .help("Fail instead of prompting for template variables without a value")
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+fn parse_key_value(value: &str) -> core::result::Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_owned(), value.to_owned())),
+        _ => Err(format!("`{value}` should be in the form KEY=VALUE")),
+    }
 }
 
 fn subcommand_add() -> Command {
@@ -83,8 +176,233 @@ fn subcommand_add() -> Command {
     ])
 }
 
+fn subcommand_scan() -> Command {
+    Command::new("scan")
+        .about("Find existing projects and add them to the database")
+        .args(&[
+            Arg::new("directory")
+                .help("The directory to search for projects in. If nothing is provided the base directory will be used")
+                .value_parser(value_parser!(PathBuf)),
+            Arg::new("depth")
+                .long("depth")
+                .help("How many directories deep to search for projects")
+                .value_parser(value_parser!(usize))
+                .default_value("5"),
+            Arg::new("yes")
+                .short('y')
+                .long("yes")
+                .help("Add every project found without prompting")
+                .action(ArgAction::SetTrue),
+        ])
+}
+
+fn subcommand_edit() -> Command {
+    Command::new("edit").about("Edit an Existing Project").args(&[
+        Arg::new("project")
+            .required_unless_present("all")
+            .help("The name, id, or directory of the project to edit"),
+        Arg::new("name").short('n').long("name").help("Project Name"),
+        Arg::new("desc").long("desc").help("Description of the project"),
+        Arg::new("directory")
+            .short('d')
+            .long("directory")
+            .help("The directory the project is located in")
+            .value_parser(value_parser!(PathBuf)),
+        Arg::new("tags")
+            .long("tag")
+            .num_args(1..)
+            .action(ArgAction::Append)
+            .help("Tags for the project, replacing the existing tags"),
+        Arg::new("add-tags")
+            .long("add-tag")
+            .num_args(1..)
+            .action(ArgAction::Append)
+            .help("Tags to add to the project"),
+        Arg::new("remove-tags")
+            .long("remove-tag")
+            .num_args(1..)
+            .action(ArgAction::Append)
+            .help("Tags to remove from the project"),
+        Arg::new("language")
+            .short('l')
+            .long("language")
+            .help("Primary programming language used"),
+        Arg::new("category")
+            .short('c')
+            .long("category")
+            .help("Used to keep similar project types together. I.E. `work`, `thirdparty`, etc"),
+        Arg::new("detect-language")
+            .long("detect-language")
+            .help("Detect the language from the files in the project directory")
+            .conflicts_with("language")
+            .action(ArgAction::SetTrue),
+        Arg::new("all")
+            .long("all")
+            .help("Detect the language of every project that doesn't have one yet")
+            .requires("detect-language")
+            .conflicts_with_all(["project", "interactive"])
+            .action(ArgAction::SetTrue),
+        Arg::new("interactive")
+            .short('i')
+            .long("interactive")
+            .action(ArgAction::SetTrue),
+    ])
+}
+
 fn subcommand_list() -> Command {
-    Command::new("list").about("List the projects in the database")
+    Command::new("list").about("List the projects in the database").args(&[
+        Arg::new("tags")
+            .long("tag")
+            .num_args(1..)
+            .action(ArgAction::Append)
+            .help("Only list projects with all of these tags"),
+        Arg::new("language")
+            .short('l')
+            .long("language")
+            .help("Only list projects using this language"),
+        Arg::new("category")
+            .short('c')
+            .long("category")
+            .help("Only list projects in this category"),
+        Arg::new("name")
+            .short('n')
+            .long("name")
+            .help("Only list projects with a name matching this glob pattern"),
+        Arg::new("search")
+            .short('s')
+            .long("search")
+            .help("Search the name, description, directory, and tags of the projects"),
+        Arg::new("sort")
+            .long("sort")
+            .help("The field to sort the projects by")
+            .value_parser(["name", "language", "category", "rowid"])
+            .default_value("rowid"),
+        Arg::new("reverse")
+            .short('r')
+            .long("reverse")
+            .help("Reverse the sort order")
+            .action(ArgAction::SetTrue),
+        Arg::new("archived")
+            .short('a')
+            .long("archived")
+            .help("Include archived projects")
+            .action(ArgAction::SetTrue),
+        Arg::new("columns")
+            .long("columns")
+            .value_delimiter(',')
+            .help("Comma separated columns to show: name, desc, tags, language, category, directory, rowid"),
+        Arg::new("format")
+            .short('f')
+            .long("format")
+            .help("The format to output the projects in")
+            .value_parser(["table", "json", "yaml", "csv", "tsv", "plain"])
+            .default_value("table"),
+    ])
+}
+
+fn subcommand_remove() -> Command {
+    Command::new("remove").about("Remove a Project from the database").args(&[
+        Arg::new("project")
+            .required(true)
+            .help("The name, id, or directory of the project to remove"),
+        Arg::new("trash")
+            .long("trash")
+            .help("Move the project directory into the trash folder in the config directory")
+            .action(ArgAction::SetTrue),
+        Arg::new("force")
+            .short('f')
+            .long("force")
+            .help("Bypass conformation prompt and remove the project")
+            .action(ArgAction::SetTrue),
+    ])
+}
+
+fn subcommand_show() -> Command {
+    Command::new("show").about("Show the details of a Project").args(&[
+        Arg::new("project")
+            .required(true)
+            .help("The name, id, or directory of the project to show. Names are fuzzy matched if there is no exact match"),
+        Arg::new("format")
+            .short('f')
+            .long("format")
+            .help("The format to output the project in")
+            .value_parser(["text", "json"])
+            .default_value("text"),
+    ])
+}
+
+fn subcommand_path() -> Command {
+    Command::new("path")
+        .about("Print the directory of a Project")
+        .args(&[Arg::new("query")
+            .required(true)
+            .help("The name, id, or directory of the project. Names are fuzzy matched if there is no exact match")])
+}
+
+fn subcommand_init_shell() -> Command {
+    Command::new("init-shell")
+        .about("Print a shell function that changes into a project directory using `path`")
+        .args(&[
+            Arg::new("shell")
+                .required(true)
+                .help("The shell to generate the function for")
+                .value_parser(["bash", "zsh", "fish"]),
+            Arg::new("name")
+                .long("name")
+                .help("The name of the generated function")
+                .default_value("pcd"),
+        ])
+}
+
+fn subcommand_doctor() -> Command {
+    Command::new("doctor")
+        .about("Find projects with missing or broken directories")
+        .args(&[Arg::new("fix")
+            .long("fix")
+            .help("Interactively fix the problems that are found")
+            .action(ArgAction::SetTrue)])
+}
+
+fn subcommand_export() -> Command {
+    Command::new("export").about("Export every project in the database").args(&[
+        Arg::new("format")
+            .short('f')
+            .long("format")
+            .help("The format to export to. Defaults to the extension of the output file, or json")
+            .value_parser(["json", "yaml", "toml"]),
+        Arg::new("output")
+            .short('o')
+            .long("output")
+            .help("The file to write to. If nothing is provided the export is printed")
+            .value_parser(value_parser!(PathBuf)),
+    ])
+}
+
+fn subcommand_import() -> Command {
+    Command::new("import").about("Import projects from an export file").args(&[
+        Arg::new("file")
+            .required(true)
+            .help("The file to import")
+            .value_parser(value_parser!(PathBuf)),
+        Arg::new("format")
+            .short('f')
+            .long("format")
+            .help("The format of the file. Defaults to the extension of the file")
+            .value_parser(["json", "yaml", "toml"]),
+        Arg::new("merge")
+            .long("merge")
+            .help("Add the projects to the database, skipping any with the same name or directory as an existing project (default)")
+            .action(ArgAction::SetTrue),
+        Arg::new("replace")
+            .long("replace")
+            .help("Remove every project in the database before importing")
+            .conflicts_with("merge")
+            .action(ArgAction::SetTrue),
+        Arg::new("dry-run")
+            .long("dry-run")
+            .help("Show what would be imported without changing the database")
+            .action(ArgAction::SetTrue),
+    ])
 }
 
 fn subcommand_reset() -> Command {
@@ -111,11 +429,44 @@ fn subcommand_config() -> Command {
                 "database-path",
                 "template-dir",
                 "template",
+                "list-columns",
             ]),
             Arg::new("value").required(true).help("The value to set"),
         ])])
 }
 
+fn subcommand_template() -> Command {
+    Command::new("template")
+        .about("Manage the Templates")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommands(vec![
+            Command::new("list").about("List the templates in the template directory"),
+            Command::new("show")
+                .about("Show the variables, files, and commands of a template")
+                .args(&[Arg::new("name").required(true).help("The name of the template")]),
+            Command::new("create")
+                .about("Create a template from an existing directory or project")
+                .args(&[
+                    Arg::new("name").required(true).help("The name of the new template"),
+                    Arg::new("from")
+                        .long("from")
+                        .required(true)
+                        .help("The directory, or the name or id of a registered project, to copy"),
+                    Arg::new("desc").long("desc").help("Description of the template"),
+                    Arg::new("replace")
+                        .long("replace")
+                        .value_name("LITERAL=VAR")
+                        .help("Replace every occurrence of LITERAL in the files and their paths with `${VAR}`")
+                        .value_parser(parse_key_value)
+                        .action(ArgAction::Append),
+                ]),
+            Command::new("validate")
+                .about("Check templates for missing files and undeclared or unused variables")
+                .args(&[Arg::new("name").help("The template to check. If nothing is provided every template is checked")]),
+        ])
+}
+
 pub fn parse() -> Result<()> {
     let matches = cli().get_matches();
 
@@ -133,11 +484,41 @@ pub fn parse() -> Result<()> {
         Some(("new", sub_matches)) => {
             commands::new::new(sub_matches, &mut config)?;
         },
+        Some(("apply", sub_matches)) => {
+            commands::apply::apply(sub_matches, &config)?;
+        },
         Some(("add", sub_matches)) => {
             commands::add::add(sub_matches, &config)?;
         },
-        Some(("list", _)) => {
-            commands::list::list(&config)?;
+        Some(("scan", sub_matches)) => {
+            commands::scan::scan(sub_matches, &config)?;
+        },
+        Some(("edit", sub_matches)) => {
+            commands::edit::edit(sub_matches, &config)?;
+        },
+        Some(("list", sub_matches)) => {
+            commands::list::list(sub_matches, &config)?;
+        },
+        Some(("remove", sub_matches)) => {
+            commands::remove::remove(sub_matches, &config)?;
+        },
+        Some(("show", sub_matches)) => {
+            commands::show::show(sub_matches, &config)?;
+        },
+        Some(("path", sub_matches)) => {
+            commands::path::path(sub_matches, &config)?;
+        },
+        Some(("init-shell", sub_matches)) => {
+            commands::init_shell::init_shell(sub_matches)?;
+        },
+        Some(("doctor", sub_matches)) => {
+            commands::doctor::doctor(sub_matches, &config)?;
+        },
+        Some(("export", sub_matches)) => {
+            commands::export::export(sub_matches, &config)?;
+        },
+        Some(("import", sub_matches)) => {
+            commands::import::import(sub_matches, &config)?;
         },
         Some(("reset", sub_matches)) => {
             commands::reset::reset(sub_matches, &config)?;
@@ -145,6 +526,9 @@ pub fn parse() -> Result<()> {
         Some(("config", sub_matches)) => {
             commands::config::config(sub_matches, &mut config)?;
         },
+        Some(("template", sub_matches)) => {
+            commands::template::template(sub_matches, &config)?;
+        },
         Some((command, _)) => {
             println!("Code has not yet been written for `{command}`");
         },
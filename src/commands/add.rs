@@ -1,11 +1,18 @@
-use crate::{config::Config, database::add_project, project::Project, utils::Result};
+use crate::{
+    commands::{run_post_register, show::print_project},
+    config::Config,
+    database::add_project,
+    detect::detect_language,
+    project::Project,
+    utils::Result,
+};
 use clap::ArgMatches;
 use console::Term;
 use dialoguer::theme::ColorfulTheme;
 use dialoguer::Input;
 use std::path::PathBuf;
 
-struct AddParams {
+pub(crate) struct AddParams {
     pub(crate) name: Option<String>,
     pub(crate) dir: Option<PathBuf>,
     pub(crate) desc: Option<String>,
@@ -53,14 +60,17 @@ pub fn add(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
         println!("A directory is required for a project, please specify one");
         return Ok(());
     }
+    if language.is_none() {
+        language = detect_language(dir.as_ref().unwrap());
+    }
     let mut project = Project::new(name, desc, tags, language, category);
     project.directory = dir;
-    add_project(config, &project)?;
-    println!("{project:#?}");
-    Ok(())
+    project.rowid = Some(add_project(config, &project)?);
+    print_project(&project);
+    run_post_register(config, &project)
 }
 
-fn add_params_interactive(
+pub(crate) fn add_params_interactive(
     name: Option<String>,
     dir: Option<PathBuf>,
     desc: Option<String>,
@@ -0,0 +1,92 @@
+use crate::{
+    commands::{dry_run::print_dry_run, new::build_options, select_project},
+    config::Config,
+    database::update_project,
+    error::Error,
+    template::{BuildSummary, HookStage, Hooks, OverwritePolicy, Template},
+    utils::{create_spinner, Result},
+};
+use clap::ArgMatches;
+use console::style;
+use std::path::PathBuf;
+
+pub fn apply(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
+    let query = sub_matches.get_one::<String>("project").cloned().unwrap();
+    let templates = sub_matches
+        .get_many::<String>("templates")
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut options = build_options(sub_matches, config)?;
+    options.policy = if sub_matches.get_flag("overwrite") {
+        OverwritePolicy::Overwrite
+    } else if sub_matches.get_flag("prompt") {
+        OverwritePolicy::Prompt
+    } else {
+        OverwritePolicy::SkipExisting
+    };
+
+    let mut project = select_project(config, &query)?;
+    let dir = match &project.directory {
+        Some(dir) if dir.is_dir() => dir.clone(),
+        _ => return Err(Error::ProjectMissingDirectory(project.name.unwrap_or(query))),
+    };
+
+    let template_files = Template::load_resolved(config, &templates)?;
+    // The project is already registered, so only the hooks around rendering run
+    let hooks = Hooks {
+        post_register: vec![],
+        ..Hooks::collect(config, &templates, &template_files)
+    };
+    if sub_matches.get_flag("dry-run") {
+        let rendered = Template::render_templates(&dir, &templates, &template_files, config, &project, &options)?;
+        return print_dry_run(&dir, &rendered, &hooks, options.policy, sub_matches.get_flag("diff"));
+    }
+
+    // Prompting for overwrites can't happen under a spinner
+    let pb = match options.policy {
+        OverwritePolicy::Prompt => None,
+        _ => Some(create_spinner("Applying Templates...")?),
+    };
+    options.output.progress = pb.clone();
+
+    hooks.run(HookStage::PreRender, &dir, &project, &options)?;
+    let summary = Template::build_templates(dir.clone(), templates.clone(), &template_files, config, &project, &options)?;
+    hooks.run(HookStage::PostRender, &dir, &project, &options)?;
+    if let Some(pb) = pb {
+        pb.finish_with_message("Templates Applied");
+    }
+
+    let mut applied = project.templates.take().unwrap_or_default();
+    for template in templates {
+        if !applied.contains(&template) {
+            applied.push(template);
+        }
+    }
+    project.templates = Some(applied);
+    update_project(config, &project)?;
+
+    print_summary(&summary);
+    Ok(())
+}
+
+fn print_summary(summary: &BuildSummary) {
+    let sections: [(&str, &Vec<PathBuf>); 4] = [
+        ("Created", &summary.created),
+        ("Changed", &summary.changed),
+        ("Unchanged", &summary.unchanged),
+        ("Skipped", &summary.skipped),
+    ];
+
+    for (title, files) in sections {
+        if files.is_empty() {
+            continue;
+        }
+        println!("{} ({})", style(title).bold(), files.len());
+        for file in files {
+            println!("  {}", file.display());
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{config::Config, error::Error, utils::Result};
+use crate::{commands::list::Column, config::Config, error::Error, utils::Result};
 use clap::ArgMatches;
 
 pub fn config(sub_matches: &ArgMatches, config: &mut Config) -> Result<()> {
@@ -17,6 +17,13 @@ pub fn config(sub_matches: &ArgMatches, config: &mut Config) -> Result<()> {
                 key if key == "template-dir" || key == "template" => {
                     config.template_dir = Some(value.into());
                 },
+                key if key == "list-columns" => {
+                    let columns = value.split(',').map(|c| c.trim().to_owned()).collect::<Vec<_>>();
+                    for column in &columns {
+                        column.parse::<Column>()?;
+                    }
+                    config.list_columns = Some(columns);
+                },
                 _ => {
                     return Err(Error::ConfigInvalidKey(key));
                 },
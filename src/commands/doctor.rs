@@ -0,0 +1,323 @@
+use crate::{
+    config::Config,
+    database::{load_projects, remove_project, update_project},
+    detect::SKIPPED_DIRS,
+    project::Project,
+    utils::Result,
+};
+use clap::ArgMatches;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::Select;
+use fs_err as fs;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// How many folders deep to look in the base directory when relocating a project
+const RELOCATE_DEPTH: usize = 6;
+
+#[derive(Debug, PartialEq)]
+enum Issue {
+    MissingName(Project),
+    MissingDirectory(Project),
+    DirectoryNotFound(Project),
+    DuplicateDirectory(Vec<Project>),
+    DuplicateName(Vec<Project>),
+    Nested { project: Project, parent: Project },
+}
+
+impl Issue {
+    fn projects(&self) -> Vec<&Project> {
+        match self {
+            Issue::MissingName(project) | Issue::MissingDirectory(project) | Issue::DirectoryNotFound(project) => {
+                vec![project]
+            },
+            Issue::DuplicateDirectory(projects) | Issue::DuplicateName(projects) => projects.iter().collect(),
+            Issue::Nested { project, parent } => vec![project, parent],
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::MissingName(project) => write!(f, "{} has no name", describe(project)),
+            Issue::MissingDirectory(project) => write!(f, "{} has no directory", describe(project)),
+            Issue::DirectoryNotFound(project) => write!(f, "the directory of {} does not exist", describe(project)),
+            Issue::DuplicateDirectory(projects) => write!(
+                f,
+                "projects share the same directory: {}",
+                projects.iter().map(describe).collect::<Vec<_>>().join(", ")
+            ),
+            Issue::DuplicateName(projects) => write!(
+                f,
+                "projects share the same name: {}",
+                projects.iter().map(describe).collect::<Vec<_>>().join(", ")
+            ),
+            Issue::Nested { project, parent } => {
+                write!(f, "{} is inside the directory of {}", describe(project), describe(parent))
+            },
+        }
+    }
+}
+
+pub fn doctor(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
+    let fix = sub_matches.get_flag("fix");
+
+    let issues = find_issues(&load_projects(config)?);
+    if issues.is_empty() {
+        println!("No problems found");
+        return Ok(());
+    }
+
+    println!("Found {} problem(s):", issues.len());
+    for issue in &issues {
+        println!("  - {issue}");
+    }
+
+    if fix {
+        // Projects removed while fixing an earlier issue can't be fixed again
+        let mut removed = Vec::new();
+        for issue in &issues {
+            if issue
+                .projects()
+                .iter()
+                .any(|project| project.rowid.is_some_and(|rowid| removed.contains(&rowid)))
+            {
+                continue;
+            }
+            println!();
+            if let Some(rowid) = fix_issue(issue, config)? {
+                removed.push(rowid);
+            }
+        }
+    } else {
+        println!("\nRun with `--fix` to fix these problems interactively");
+    }
+
+    Ok(())
+}
+
+fn find_issues(projects: &[Project]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let active = projects
+        .iter()
+        .filter(|project| !project.archived.unwrap_or_default())
+        .collect::<Vec<_>>();
+
+    for project in &active {
+        if project.name.is_none() {
+            issues.push(Issue::MissingName((*project).clone()));
+        }
+        match &project.directory {
+            None => issues.push(Issue::MissingDirectory((*project).clone())),
+            Some(dir) if !dir.is_dir() => issues.push(Issue::DirectoryNotFound((*project).clone())),
+            _ => {},
+        }
+    }
+
+    issues.extend(duplicates(&active, |project| project.directory.clone()).map(Issue::DuplicateDirectory));
+    issues.extend(duplicates(&active, |project| project.name.clone()).map(Issue::DuplicateName));
+
+    for project in &active {
+        let dir = match &project.directory {
+            Some(dir) => dir,
+            None => continue,
+        };
+        let parent = active.iter().find(|other| match &other.directory {
+            Some(other_dir) => dir != other_dir && dir.starts_with(other_dir),
+            None => false,
+        });
+        if let Some(parent) = parent {
+            issues.push(Issue::Nested {
+                project: (*project).clone(),
+                parent: (*parent).clone(),
+            });
+        }
+    }
+
+    issues
+}
+
+/// Group the projects that share the same key, skipping projects without one
+fn duplicates<K: PartialEq>(projects: &[&Project], key: impl Fn(&Project) -> Option<K>) -> impl Iterator<Item = Vec<Project>> {
+    let mut groups: Vec<(K, Vec<Project>)> = Vec::new();
+    for project in projects {
+        let k = match key(project) {
+            Some(k) => k,
+            None => continue,
+        };
+        match groups.iter_mut().find(|(group_key, _)| *group_key == k) {
+            Some((_, group)) => group.push((*project).clone()),
+            None => groups.push((k, vec![(*project).clone()])),
+        }
+    }
+    groups.into_iter().map(|(_, group)| group).filter(|group| group.len() > 1)
+}
+
+/// Ask the user how to fix an issue, returning the id of the project if it was removed
+fn fix_issue(issue: &Issue, config: &Config) -> Result<Option<i64>> {
+    println!("{issue}");
+
+    let project = match issue {
+        Issue::MissingName(project) | Issue::MissingDirectory(project) | Issue::Nested { project, .. } => project,
+        Issue::DirectoryNotFound(project) => return fix_directory_not_found(project, config),
+        Issue::DuplicateDirectory(projects) | Issue::DuplicateName(projects) => {
+            let mut items = projects
+                .iter()
+                .map(|project| format!("Remove {}", describe(project)))
+                .collect::<Vec<_>>();
+            items.push("Skip".to_owned());
+
+            let selection = select(&items)?;
+            return match projects.get(selection) {
+                Some(project) => {
+                    remove_project(config, project)?;
+                    Ok(project.rowid)
+                },
+                None => Ok(None),
+            };
+        },
+    };
+
+    match select(&["Remove the project", "Archive the project", "Skip"])? {
+        0 => {
+            remove_project(config, project)?;
+            Ok(project.rowid)
+        },
+        1 => {
+            archive(project, config)?;
+            Ok(None)
+        },
+        _ => Ok(None),
+    }
+}
+
+fn fix_directory_not_found(project: &Project, config: &Config) -> Result<Option<i64>> {
+    let name = project
+        .directory
+        .as_ref()
+        .and_then(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy().to_string());
+    let candidates = match (&config.base_dir, name) {
+        (Some(base_dir), Some(name)) => find_dirs_named(base_dir, &name, RELOCATE_DEPTH),
+        _ => vec![],
+    };
+
+    let mut items = candidates
+        .iter()
+        .map(|dir| format!("Relocate to {}", dir.display()))
+        .collect::<Vec<_>>();
+    items.extend(["Remove the project", "Archive the project", "Skip"].map(str::to_owned));
+
+    let selection = select(&items)?;
+    match selection.checked_sub(candidates.len()) {
+        None => {
+            let mut project = project.clone();
+            project.directory = Some(candidates[selection].clone());
+            update_project(config, &project)?;
+            Ok(None)
+        },
+        Some(0) => {
+            remove_project(config, project)?;
+            Ok(project.rowid)
+        },
+        Some(1) => {
+            archive(project, config)?;
+            Ok(None)
+        },
+        _ => Ok(None),
+    }
+}
+
+fn archive(project: &Project, config: &Config) -> Result<()> {
+    let mut project = project.clone();
+    project.archived = Some(true);
+    update_project(config, &project)
+}
+
+fn select<T: ToString>(items: &[T]) -> Result<usize> {
+    Ok(Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("How should this be fixed?")
+        .items(items)
+        .default(items.len() - 1)
+        .interact()?)
+}
+
+fn find_dirs_named(dir: &Path, name: &str, depth: usize) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return found,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let entry_name = entry.file_name().to_string_lossy().to_string();
+        if !path.is_dir() || entry_name.starts_with('.') || SKIPPED_DIRS.contains(&entry_name.as_str()) {
+            continue;
+        }
+        if entry_name == name {
+            found.push(path);
+        } else if depth > 0 {
+            found.extend(find_dirs_named(&path, name, depth - 1));
+        }
+    }
+
+    found.sort();
+    found
+}
+
+fn describe(project: &Project) -> String {
+    format!(
+        "`{}` (id {})",
+        project.name.as_deref().unwrap_or("<no name>"),
+        project.rowid.map(|id| id.to_string()).unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(rowid: i64, name: Option<&str>, directory: Option<&str>) -> Project {
+        Project {
+            rowid: Some(rowid),
+            name: name.map(str::to_owned),
+            directory: directory.map(PathBuf::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_find_issues() {
+        let dir = "tests/templates";
+        let nested = "tests/templates/template_dir";
+        let projects = vec![
+            project(1, Some("one"), Some(dir)),
+            project(2, Some("two"), Some(dir)),
+            project(3, Some("one"), Some(nested)),
+            project(4, None, Some("tests/missing")),
+            project(5, Some("five"), None),
+            Project {
+                archived: Some(true),
+                ..project(6, Some("archived"), Some("tests/missing"))
+            },
+        ];
+
+        let issues = find_issues(&projects);
+        assert_eq!(
+            issues,
+            vec![
+                Issue::MissingName(projects[3].clone()),
+                Issue::DirectoryNotFound(projects[3].clone()),
+                Issue::MissingDirectory(projects[4].clone()),
+                Issue::DuplicateDirectory(vec![projects[0].clone(), projects[1].clone()]),
+                Issue::DuplicateName(vec![projects[0].clone(), projects[2].clone()]),
+                Issue::Nested {
+                    project: projects[2].clone(),
+                    parent: projects[0].clone(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{
+    template::{Hooks, OverwritePolicy, RenderedTemplate},
+    utils::Result,
+};
+use console::style;
+use fs_err as fs;
+use similar::{ChangeTag, TextDiff};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// What writing a rendered file would do to the project directory
+#[derive(Debug, PartialEq, Eq)]
+enum FileStatus {
+    New,
+    Changed,
+    Unchanged,
+    Skipped,
+    Prompt,
+}
+
+impl FileStatus {
+    fn label(&self) -> String {
+        match self {
+            FileStatus::New => style("new").green().to_string(),
+            FileStatus::Changed => style("overwritten").yellow().to_string(),
+            FileStatus::Unchanged => style("unchanged").dim().to_string(),
+            FileStatus::Skipped => style("exists, skipped").dim().to_string(),
+            FileStatus::Prompt => style("exists, will ask").yellow().to_string(),
+        }
+    }
+}
+
+/// Print what building the rendered templates in `dir` would do, without writing or running anything
+pub fn print_dry_run(
+    dir: &Path,
+    rendered: &[RenderedTemplate],
+    hooks: &Hooks,
+    policy: OverwritePolicy,
+    show_diff: bool,
+) -> Result<()> {
+    let plan = plan_files(dir, rendered, policy);
+
+    let mut statuses = Vec::new();
+    let mut diffs = Vec::new();
+    for (file, planned) in &plan {
+        statuses.push((file.as_path(), planned.status.label()));
+        let diff = match planned.status {
+            FileStatus::Changed => planned.existing.as_ref().zip(planned.current.as_ref()),
+            FileStatus::Skipped | FileStatus::Prompt => planned.current.as_ref().zip(planned.proposed.as_ref()),
+            FileStatus::New | FileStatus::Unchanged => None,
+        };
+        if let Some((old, new)) = diff {
+            diffs.push((file, old, new));
+        }
+    }
+
+    println!("{}", style(dir.display()).bold());
+    for line in tree_lines(&statuses) {
+        println!("{line}");
+    }
+
+    if show_diff {
+        for (file, old, new) in diffs {
+            let name = file.display().to_string();
+            let diff = TextDiff::from_lines(old.as_str(), new.as_str());
+            println!();
+            println!("{}", style(format!("--- a/{name}")).bold());
+            println!("{}", style(format!("+++ b/{name}")).bold());
+            for hunk in diff.unified_diff().context_radius(3).iter_hunks() {
+                println!("{}", style(hunk.header()).cyan());
+                for change in hunk.iter_changes() {
+                    let line = format!("{}{}", change.tag(), change.to_string_lossy().trim_end_matches('\n'));
+                    match change.tag() {
+                        ChangeTag::Delete => println!("{}", style(line).red()),
+                        ChangeTag::Insert => println!("{}", style(line).green()),
+                        ChangeTag::Equal => println!("{line}"),
+                    }
+                }
+            }
+        }
+    }
+
+    let commands = rendered
+        .iter()
+        .flat_map(|template| template.commands.iter().map(move |command| (&template.name, command)))
+        .collect::<Vec<_>>();
+    if !commands.is_empty() {
+        println!();
+        println!("{}", style("Commands:").bold());
+        for (template, command) in commands {
+            let mut notes = vec![template.clone()];
+            if let Ok(spec) = command.spec() {
+                if let Some(path) = spec.if_missing {
+                    notes.push(format!("unless `{}` exists", path.display()));
+                }
+                if spec.retries > 0 {
+                    notes.push(format!("retried up to {} times", spec.retries));
+                }
+                if spec.allow_failure {
+                    notes.push("allowed to fail".to_owned());
+                }
+            }
+            println!("  {command} {}", style(format!("({})", notes.join(", "))).dim());
+        }
+    }
+
+    if !hooks.is_empty() {
+        println!();
+        println!("{}", style("Hooks:").bold());
+        for (stage, command) in hooks.stages() {
+            println!("  {command} {}", style(format!("({stage})")).dim());
+        }
+    }
+
+    Ok(())
+}
+
+/// What building the templates would do to one file
+#[derive(Debug)]
+struct PlannedFile {
+    /// The file as it is on disk now
+    existing: Option<String>,
+    /// The file once every template has been written
+    current: Option<String>,
+    /// The last version a template would have written but didn't, because of the overwrite policy
+    proposed: Option<String>,
+    status: FileStatus,
+}
+
+/// Replay the writes of each template in order, following the overwrite policy the same way `write_files` does
+///
+/// A file written by an earlier template counts as existing for later ones. Prompts are assumed to be declined.
+fn plan_files(dir: &Path, rendered: &[RenderedTemplate], policy: OverwritePolicy) -> BTreeMap<PathBuf, PlannedFile> {
+    let mut plan: BTreeMap<PathBuf, PlannedFile> = BTreeMap::new();
+
+    for (file, contents) in rendered.iter().flat_map(|template| template.files.iter()) {
+        let planned = plan.entry(file.clone()).or_insert_with(|| {
+            let existing = fs::read_to_string(dir.join(file)).ok();
+            PlannedFile {
+                status: match existing {
+                    Some(_) => FileStatus::Unchanged,
+                    None => FileStatus::New,
+                },
+                current: existing.clone(),
+                existing,
+                proposed: None,
+            }
+        });
+
+        match &planned.current {
+            None => planned.current = Some(contents.clone()),
+            Some(current) if current == contents => {},
+            Some(_) => match policy {
+                OverwritePolicy::Overwrite => planned.current = Some(contents.clone()),
+                OverwritePolicy::SkipExisting => planned.proposed = Some(contents.clone()),
+                OverwritePolicy::Prompt => {
+                    planned.proposed = Some(contents.clone());
+                    planned.status = FileStatus::Prompt;
+                },
+            },
+        }
+    }
+
+    for planned in plan.values_mut() {
+        if planned.existing.is_none() || planned.status == FileStatus::Prompt {
+            continue;
+        }
+        planned.status = if planned.current != planned.existing {
+            FileStatus::Changed
+        } else if planned.proposed.is_some() {
+            FileStatus::Skipped
+        } else {
+            FileStatus::Unchanged
+        };
+    }
+
+    plan
+}
+
+/// Lay out sorted file paths as an indented tree, listing each directory once above its contents
+fn tree_lines(files: &[(&Path, String)]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut open_dirs: Vec<String> = Vec::new();
+
+    for (file, label) in files {
+        let mut components = file.iter().map(|c| c.to_string_lossy().to_string()).collect::<Vec<_>>();
+        let name = components.pop().unwrap_or_default();
+
+        let shared = open_dirs.iter().zip(&components).take_while(|(a, b)| a == b).count();
+        open_dirs.truncate(shared);
+        for dir in &components[shared..] {
+            lines.push(format!("{}{dir}/", "  ".repeat(open_dirs.len() + 1)));
+            open_dirs.push(dir.clone());
+        }
+
+        lines.push(format!("{}{name}  ({label})", "  ".repeat(open_dirs.len() + 1)));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_files() -> anyhow::Result<()> {
+        let test_dir = Path::new("tests/test_files/dry_run");
+        if test_dir.exists() {
+            fs::remove_dir_all(test_dir)?;
+        }
+        fs::create_dir_all(test_dir)?;
+        fs::write(test_dir.join("kept.txt"), "on disk")?;
+
+        let template = |name: &str, contents: &str| RenderedTemplate {
+            name: name.to_owned(),
+            files: vec![
+                (PathBuf::from("shared.txt"), contents.to_owned()),
+                (PathBuf::from("kept.txt"), contents.to_owned()),
+            ],
+            commands: vec![],
+            env: BTreeMap::new(),
+        };
+        let rendered = [template("first", "first"), template("second", "second")];
+
+        // The first template creates the file, so the second one is skipped like it would be when writing
+        let plan = plan_files(test_dir, &rendered, OverwritePolicy::SkipExisting);
+        assert_eq!(plan[Path::new("shared.txt")].status, FileStatus::New);
+        assert_eq!(plan[Path::new("shared.txt")].current.as_deref(), Some("first"));
+        assert_eq!(plan[Path::new("kept.txt")].status, FileStatus::Skipped);
+        assert_eq!(plan[Path::new("kept.txt")].current.as_deref(), Some("on disk"));
+
+        let plan = plan_files(test_dir, &rendered, OverwritePolicy::Overwrite);
+        assert_eq!(plan[Path::new("shared.txt")].status, FileStatus::New);
+        assert_eq!(plan[Path::new("shared.txt")].current.as_deref(), Some("second"));
+        assert_eq!(plan[Path::new("kept.txt")].status, FileStatus::Changed);
+
+        // Writing the second template would ask about the file the first one created
+        let plan = plan_files(test_dir, &rendered, OverwritePolicy::Prompt);
+        assert_eq!(plan[Path::new("shared.txt")].status, FileStatus::Prompt);
+        assert_eq!(plan[Path::new("kept.txt")].status, FileStatus::Prompt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tree_lines() {
+        let files = [
+            (Path::new("a/b/one.txt"), "new".to_owned()),
+            (Path::new("a/b/two.txt"), "new".to_owned()),
+            (Path::new("a/c.txt"), "unchanged".to_owned()),
+            (Path::new("d.txt"), "new".to_owned()),
+        ];
+
+        assert_eq!(
+            tree_lines(&files),
+            vec![
+                "  a/",
+                "    b/",
+                "      one.txt  (new)",
+                "      two.txt  (new)",
+                "    c.txt  (unchanged)",
+                "  d.txt  (new)",
+            ]
+        );
+    }
+}
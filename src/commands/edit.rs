@@ -0,0 +1,100 @@
+use crate::{
+    commands::{add::add_params_interactive, select_project, show::print_project},
+    config::Config,
+    database,
+    detect::detect_language,
+    utils::Result,
+};
+use clap::ArgMatches;
+use std::path::PathBuf;
+
+pub fn edit(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
+    if sub_matches.get_flag("all") {
+        return backfill_languages(config);
+    }
+
+    let query = sub_matches.get_one::<String>("project").cloned().unwrap();
+    let mut project = select_project(config, &query)?;
+
+    if let Some(name) = sub_matches.get_one::<String>("name") {
+        project.name = Some(name.clone());
+    }
+    if let Some(dir) = sub_matches.get_one::<PathBuf>("directory") {
+        project.directory = Some(dir.clone());
+    }
+    if let Some(desc) = sub_matches.get_one::<String>("desc") {
+        project.desc = Some(desc.clone());
+    }
+    if let Some(language) = sub_matches.get_one::<String>("language") {
+        project.language = Some(language.clone());
+    }
+    if sub_matches.get_flag("detect-language") {
+        match project.directory.as_deref().and_then(detect_language) {
+            Some(language) => project.language = Some(language),
+            None => println!("Unable to detect the language of the project"),
+        }
+    }
+    if let Some(category) = sub_matches.get_one::<String>("category") {
+        project.category = Some(category.clone());
+    }
+
+    let mut tags = match sub_matches.get_many::<String>("tags") {
+        Some(tags) => tags.cloned().collect(),
+        None => project.tags.take().unwrap_or_default(),
+    };
+    for tag in sub_matches.get_many::<String>("add-tags").into_iter().flatten() {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+    let remove_tags = sub_matches
+        .get_many::<String>("remove-tags")
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    tags.retain(|tag| !remove_tags.contains(&tag));
+    project.tags = Some(tags);
+
+    let interactive = sub_matches.get_flag("interactive");
+    if interactive {
+        let new_params = add_params_interactive(
+            project.name.take(),
+            project.directory.take(),
+            project.desc.take(),
+            project.tags.take().unwrap_or_default(),
+            project.language.take(),
+            project.category.take(),
+        )?;
+
+        project.name = new_params.name;
+        project.directory = new_params.dir;
+        project.desc = new_params.desc;
+        project.tags = Some(new_params.tags);
+        project.language = new_params.language;
+        project.category = new_params.category;
+    }
+
+    database::update_project(config, &project)?;
+    print_project(&project);
+    Ok(())
+}
+
+fn backfill_languages(config: &Config) -> Result<()> {
+    let mut updated = 0;
+
+    for mut project in database::load_projects(config)? {
+        if project.language.is_some() {
+            continue;
+        }
+
+        if let Some(language) = project.directory.as_deref().and_then(detect_language) {
+            println!("{}: {language}", project.name.as_deref().unwrap_or_default());
+            project.language = Some(language);
+            database::update_project(config, &project)?;
+            updated += 1;
+        }
+    }
+
+    println!("Detected the language of {updated} project(s)");
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use crate::{
+    config::Config,
+    database::load_projects,
+    project::Project,
+    utils::{Format, Result},
+};
+use clap::ArgMatches;
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// The file layout used for exports, TOML doesn't allow a list at the top level
+/// so every format wraps the projects in a table
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Catalog {
+    pub projects: Vec<Project>,
+}
+
+impl Catalog {
+    pub fn serialize(&self, format: Format) -> Result<String> {
+        Ok(match format {
+            Format::Json => serde_json::to_string_pretty(self)? + "\n",
+            Format::Yaml => serde_yaml::to_string(self)?,
+            Format::Toml => toml::to_string(self)?,
+        })
+    }
+
+    pub fn parse(contents: &str, format: Format) -> Result<Self> {
+        Ok(match format {
+            Format::Json => serde_json::from_str(contents)?,
+            Format::Yaml => serde_yaml::from_str(contents)?,
+            Format::Toml => toml::from_str(contents)?,
+        })
+    }
+}
+
+pub fn export(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
+    let output = sub_matches.get_one::<PathBuf>("output").cloned();
+    let format = match sub_matches.get_one::<String>("format") {
+        Some(format) => Format::from_name(format).unwrap(),
+        None => match &output {
+            Some(output) => Format::from_path(output)?,
+            None => Format::Json,
+        },
+    };
+
+    let catalog = Catalog {
+        projects: load_projects(config)?,
+    };
+    let contents = catalog.serialize(format)?;
+
+    match output {
+        Some(output) => {
+            fs::write(&output, contents)?;
+            println!("Exported {} project(s) to `{}`", catalog.projects.len(), output.display());
+        },
+        None => print!("{contents}"),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let catalog = Catalog {
+            projects: vec![
+                Project {
+                    name: Some("Test".to_owned()),
+                    desc: Some("a test project".to_owned()),
+                    directory: Some(PathBuf::from("/dev/test")),
+                    tags: Some(vec!["test".to_owned(), "project".to_owned()]),
+                    language: Some("rust".to_owned()),
+                    ..Default::default()
+                },
+                Project {
+                    name: Some("Empty".to_owned()),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        for format in [Format::Json, Format::Yaml, Format::Toml] {
+            let contents = catalog.serialize(format)?;
+            assert_eq!(Catalog::parse(&contents, format)?, catalog);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    commands::{export::Catalog, run_post_register},
+    config::Config,
+    database::{import_projects, load_projects},
+    project::Project,
+    utils::{Format, Result},
+};
+use clap::ArgMatches;
+use fs_err as fs;
+use std::path::PathBuf;
+
+pub fn import(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
+    let file = sub_matches.get_one::<PathBuf>("file").cloned().unwrap();
+    let replace = sub_matches.get_flag("replace");
+    let dry_run = sub_matches.get_flag("dry-run");
+    let format = match sub_matches.get_one::<String>("format") {
+        Some(format) => Format::from_name(format).unwrap(),
+        None => Format::from_path(&file)?,
+    };
+
+    let catalog = Catalog::parse(&fs::read_to_string(&file)?, format)?;
+
+    // When replacing, the existing projects are removed so only the file can conflict with itself
+    let mut known = if replace { vec![] } else { load_projects(config)? };
+    let mut added = Vec::new();
+    let mut skipped = Vec::new();
+
+    for mut project in catalog.projects {
+        project.rowid = None;
+        match known.iter().find(|existing| same_directory(existing, &project)) {
+            Some(existing) => skipped.push((project, existing.clone())),
+            None => {
+                known.push(project.clone());
+                added.push(project);
+            },
+        }
+    }
+
+    let prefix = if dry_run { "Would add" } else { "Adding" };
+    for project in &added {
+        println!("{prefix} `{}`", describe(project));
+        // Several projects can share a name, but it's worth knowing about since names are used to find them
+        let namesakes = known
+            .iter()
+            .filter(|other| project.name.is_some() && other.name == project.name && !same_directory(other, project))
+            .count();
+        if namesakes > 0 {
+            println!(
+                "  `{}` is also the name of {namesakes} other project(s)",
+                project.name.as_deref().unwrap()
+            );
+        }
+    }
+    for (project, existing) in &skipped {
+        println!(
+            "Skipping `{}`, it has the same directory as `{}`",
+            describe(project),
+            describe(existing)
+        );
+    }
+
+    if dry_run {
+        if replace {
+            println!("Would remove every project currently in the database");
+        }
+        return Ok(());
+    }
+
+    let rowids = import_projects(config, &added, replace)?;
+    for (project, rowid) in added.iter_mut().zip(rowids) {
+        project.rowid = Some(rowid);
+        run_post_register(config, project)?;
+    }
+
+    println!("Imported {} project(s), skipped {}", added.len(), skipped.len());
+    Ok(())
+}
+
+/// Two projects conflict when they have the same directory, names don't have to be unique
+fn same_directory(a: &Project, b: &Project) -> bool {
+    a.directory.is_some() && a.directory == b.directory
+}
+
+fn describe(project: &Project) -> String {
+    format!(
+        "{} ({})",
+        project.name.as_deref().unwrap_or_default(),
+        project
+            .directory
+            .as_ref()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default()
+    )
+}
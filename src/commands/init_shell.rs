@@ -0,0 +1,31 @@
+use crate::utils::Result;
+use clap::ArgMatches;
+
+pub fn init_shell(sub_matches: &ArgMatches) -> Result<()> {
+    let shell = sub_matches.get_one::<String>("shell").cloned().unwrap();
+    let name = sub_matches.get_one::<String>("name").cloned().unwrap();
+
+    print!("{}", shell_function(&shell, &name, env!("CARGO_BIN_NAME")));
+
+    Ok(())
+}
+
+fn shell_function(shell: &str, name: &str, bin: &str) -> String {
+    match shell {
+        "fish" => format!(
+            "function {name} --description 'Jump to a project directory'
+    set -l dir ({bin} path $argv); or return
+    cd $dir
+end
+"
+        ),
+        _ => format!(
+            "{name}() {{
+    local dir
+    dir=\"$({bin} path \"$@\")\" || return
+    cd \"$dir\"
+}}
+"
+        ),
+    }
+}
@@ -1,18 +1,277 @@
-use crate::{config::Config, database::load_projects, utils::Result};
-use prettytable::{format, row, Table};
+use crate::{
+    config::Config,
+    database::{query_projects, ProjectFilter, SortKey},
+    error::Error,
+    project::Project,
+    utils::Result,
+};
+use clap::ArgMatches;
+use prettytable::{format, Cell, Row, Table};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::str::FromStr;
 
-pub fn list(config: &Config) -> Result<()> {
-    let projects = load_projects(config)?;
+const DEFAULT_COLUMNS: [Column; 3] = [Column::Name, Column::Desc, Column::Directory];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Name,
+    Desc,
+    Tags,
+    Language,
+    Category,
+    Directory,
+    Rowid,
+}
+
+impl Column {
+    fn key(&self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::Desc => "desc",
+            Column::Tags => "tags",
+            Column::Language => "language",
+            Column::Category => "category",
+            Column::Directory => "directory",
+            Column::Rowid => "rowid",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Column::Name => "Name",
+            Column::Desc => "Description",
+            Column::Tags => "Tags",
+            Column::Language => "Language",
+            Column::Category => "Category",
+            Column::Directory => "Directory",
+            Column::Rowid => "ID",
+        }
+    }
+
+    fn value(&self, project: &Project) -> String {
+        match self {
+            Column::Name => project.name.clone().unwrap_or_default(),
+            Column::Desc => project.desc.clone().unwrap_or_default(),
+            Column::Tags => project.tags.clone().unwrap_or_default().join(", "),
+            Column::Language => project.language.clone().unwrap_or_default(),
+            Column::Category => project.category.clone().unwrap_or_default(),
+            Column::Directory => project
+                .directory
+                .as_ref()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default(),
+            Column::Rowid => project.rowid.map(|id| id.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "name" => Ok(Column::Name),
+            "desc" | "description" => Ok(Column::Desc),
+            "tags" => Ok(Column::Tags),
+            "language" => Ok(Column::Language),
+            "category" => Ok(Column::Category),
+            "directory" | "dir" => Ok(Column::Directory),
+            "rowid" | "id" => Ok(Column::Rowid),
+            _ => Err(Error::InvalidColumn(s.to_owned())),
+        }
+    }
+}
+
+/// A project serialized with only the selected columns, in the order they were given
+struct ProjectRow<'a> {
+    project: &'a Project,
+    columns: &'a [Column],
+}
+
+impl Serialize for ProjectRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for column in self.columns {
+            match column {
+                Column::Name => map.serialize_entry(column.key(), &self.project.name)?,
+                Column::Desc => map.serialize_entry(column.key(), &self.project.desc)?,
+                Column::Tags => map.serialize_entry(column.key(), &self.project.tags)?,
+                Column::Language => map.serialize_entry(column.key(), &self.project.language)?,
+                Column::Category => map.serialize_entry(column.key(), &self.project.category)?,
+                Column::Directory => map.serialize_entry(column.key(), &self.project.directory)?,
+                Column::Rowid => map.serialize_entry(column.key(), &self.project.rowid)?,
+            }
+        }
+        map.end()
+    }
+}
+
+pub fn list(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
+    let sort = match sub_matches.get_one::<String>("sort").map(String::as_str) {
+        Some("name") => SortKey::Name,
+        Some("language") => SortKey::Language,
+        Some("category") => SortKey::Category,
+        _ => SortKey::Rowid,
+    };
+
+    let filter = ProjectFilter {
+        tags: sub_matches
+            .get_many::<String>("tags")
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect(),
+        language: sub_matches.get_one::<String>("language").cloned(),
+        category: sub_matches.get_one::<String>("category").cloned(),
+        name: sub_matches.get_one::<String>("name").cloned(),
+        search: sub_matches.get_one::<String>("search").cloned(),
+        sort,
+        reverse: sub_matches.get_flag("reverse"),
+        archived: sub_matches.get_flag("archived"),
+    };
+
+    let columns = match sub_matches.get_many::<String>("columns") {
+        Some(columns) => columns.map(|c| c.parse()).collect::<Result<Vec<Column>>>()?,
+        None => match &config.list_columns {
+            Some(columns) => columns.iter().map(|c| c.parse()).collect::<Result<Vec<Column>>>()?,
+            None => DEFAULT_COLUMNS.to_vec(),
+        },
+    };
+
+    let projects = query_projects(config, &filter)?;
+    let format = sub_matches.get_one::<String>("format").cloned().unwrap_or_default();
+
+    print!("{}", format_projects(&projects, &columns, &format)?);
+    Ok(())
+}
+
+fn format_projects(projects: &[Project], columns: &[Column], format: &str) -> Result<String> {
+    let rows = projects
+        .iter()
+        .map(|project| ProjectRow { project, columns })
+        .collect::<Vec<_>>();
+
+    let output = match format {
+        "json" => serde_json::to_string_pretty(&rows)? + "\n",
+        "yaml" => serde_yaml::to_string(&rows)?,
+        "csv" => format_delimited(projects, columns, ',', csv_escape),
+        "tsv" => format_delimited(projects, columns, '\t', |value| value.replace(['\t', '\n', '\r'], " ")),
+        "plain" => {
+            let mut table = build_table(projects, columns);
+            table.set_format(*format::consts::FORMAT_CLEAN);
+            table.unset_titles();
+            table.to_string()
+        },
+        _ => build_table(projects, columns).to_string(),
+    };
+
+    Ok(output)
+}
+
+fn build_table(projects: &[Project], columns: &[Column]) -> Table {
     let mut table = Table::new();
     table.set_format(*format::consts::FORMAT_BOX_CHARS);
-    table.set_titles(row!["Name", "Description", "Directory"]);
+    table.set_titles(Row::new(columns.iter().map(|c| Cell::new(c.title())).collect()));
     for project in projects {
-        table.add_row(row![
-            project.name.unwrap_or_default(),
-            project.desc.unwrap_or_default(),
-            project.directory.unwrap_or_default().display()
-        ]);
+        table.add_row(Row::new(columns.iter().map(|c| Cell::new(&c.value(project))).collect()));
+    }
+    table
+}
+
+fn format_delimited(projects: &[Project], columns: &[Column], delimiter: char, escape: fn(&str) -> String) -> String {
+    let mut output = String::new();
+
+    let header = columns.iter().map(|c| c.key()).collect::<Vec<_>>();
+    output.push_str(&header.join(&delimiter.to_string()));
+    output.push('\n');
+
+    for project in projects {
+        let values = columns.iter().map(|c| escape(&c.value(project))).collect::<Vec<_>>();
+        output.push_str(&values.join(&delimiter.to_string()));
+        output.push('\n');
+    }
+
+    output
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn test_projects() -> Vec<Project> {
+        vec![
+            Project {
+                rowid: Some(1),
+                name: Some("alpha".to_owned()),
+                desc: Some("Says \"hi\", twice".to_owned()),
+                directory: Some(PathBuf::from("/dev/alpha")),
+                tags: Some(vec!["cli".to_owned(), "rust".to_owned()]),
+                ..Default::default()
+            },
+            Project {
+                rowid: Some(2),
+                name: Some("beta".to_owned()),
+                desc: Some("tabs\tand\nnewlines".to_owned()),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        assert_eq!("Name".parse::<Column>().unwrap(), Column::Name);
+        assert_eq!("description".parse::<Column>().unwrap(), Column::Desc);
+        assert_eq!("dir".parse::<Column>().unwrap(), Column::Directory);
+        assert_eq!("id".parse::<Column>().unwrap(), Column::Rowid);
+        assert!(matches!("size".parse::<Column>(), Err(Error::InvalidColumn(column)) if column == "size"));
+    }
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("a, b"), "\"a, b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_escape("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn test_format_delimited() {
+        let projects = test_projects();
+        let columns = [Column::Rowid, Column::Name, Column::Desc, Column::Tags];
+
+        assert_eq!(
+            format_projects(&projects, &columns, "csv").unwrap(),
+            "rowid,name,desc,tags\n1,alpha,\"Says \"\"hi\"\", twice\",\"cli, rust\"\n2,beta,\"tabs\tand\nnewlines\",\n"
+        );
+        assert_eq!(
+            format_projects(&projects, &columns, "tsv").unwrap(),
+            "rowid\tname\tdesc\ttags\n1\talpha\tSays \"hi\", twice\tcli, rust\n2\tbeta\ttabs and newlines\t\n"
+        );
+    }
+
+    #[test]
+    fn test_format_serialized() {
+        let projects = test_projects();
+        let columns = [Column::Name, Column::Rowid, Column::Directory];
+
+        // Keys follow the order the columns were given in, not the order of the fields
+        let json = format_projects(&projects[..1], &columns, "json").unwrap();
+        let keys = ["\"name\"", "\"rowid\"", "\"directory\""].map(|key| json.find(key).unwrap());
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(!json.contains("\"desc\""));
+
+        assert_eq!(
+            format_projects(&projects, &columns, "yaml").unwrap(),
+            "- name: alpha\n  rowid: 1\n  directory: /dev/alpha\n- name: beta\n  rowid: 2\n  directory: null\n"
+        );
     }
-    table.printstd();
-    Ok(())
 }
@@ -1,5 +1,118 @@
+use crate::{
+    config::Config,
+    database::{find_projects, load_projects},
+    error::Error,
+    project::Project,
+    template::{BuildOptions, CommandOutput, HookStage},
+    utils::{fuzzy_score, Result},
+};
+use console::Term;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::Select;
+
 pub mod add;
+pub mod apply;
 pub mod config;
+pub mod doctor;
+pub mod dry_run;
+pub mod edit;
+pub mod export;
+pub mod import;
+pub mod init_shell;
 pub mod list;
 pub mod new;
+pub mod path;
+pub mod remove;
 pub mod reset;
+pub mod scan;
+pub mod show;
+pub mod template;
+
+/// Resolve `query` to a single project by id, name, or directory.
+/// When more than one project matches, the user is asked to pick one.
+pub fn select_project(config: &Config, query: &str) -> Result<Project> {
+    pick_project(find_projects(config, query)?, query)
+}
+
+/// Like [`select_project`], but falls back to a fuzzy match on the name when nothing matches exactly.
+/// Only for commands that don't change anything, since a fuzzy match may not be the project that was meant.
+pub fn select_project_fuzzy(config: &Config, query: &str) -> Result<Project> {
+    let mut projects = find_projects(config, query)?;
+
+    if projects.is_empty() {
+        let mut scored = load_projects(config)?
+            .into_iter()
+            .filter_map(|project| {
+                let score = fuzzy_score(query, project.name.as_deref().unwrap_or_default())?;
+                Some((score, project))
+            })
+            .collect::<Vec<_>>();
+        scored.sort_by(|(a, _), (b, _)| b.cmp(a));
+        projects = scored.into_iter().map(|(_, project)| project).collect();
+    }
+
+    pick_project(projects, query)
+}
+
+fn pick_project(mut projects: Vec<Project>, query: &str) -> Result<Project> {
+    match projects.len() {
+        0 => Err(Error::ProjectNotFound(query.to_owned())),
+        1 => Ok(projects.remove(0)),
+        _ => {
+            // Prompt on stderr so stdout can still be captured by scripts
+            let term = Term::stderr();
+            if !term.is_term() {
+                return Err(Error::ProjectAmbiguous(query.to_owned()));
+            }
+
+            let items = projects
+                .iter()
+                .map(|project| {
+                    format!(
+                        "{} ({})",
+                        project.name.as_deref().unwrap_or_default(),
+                        project
+                            .directory
+                            .as_ref()
+                            .map(|dir| dir.display().to_string())
+                            .unwrap_or_default()
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let selection = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Multiple projects match `{query}`"))
+                .items(&items)
+                .default(0)
+                .interact_on_opt(&term)?;
+
+            match selection {
+                Some(index) => Ok(projects.remove(index)),
+                None => Err(Error::ProjectNotFound(query.to_owned())),
+            }
+        },
+    }
+}
+
+/// Run the `post_register` hooks from the config for a project that was added without templates
+///
+/// They run in the project directory when it exists, since a registered directory doesn't have to.
+pub(crate) fn run_post_register(config: &Config, project: &Project) -> Result<()> {
+    let hooks = match &config.hooks {
+        Some(hooks) if !hooks.post_register.is_empty() => hooks,
+        _ => return Ok(()),
+    };
+
+    let cwd = match &project.directory {
+        Some(dir) if dir.is_dir() => dir.clone(),
+        _ => std::env::current_dir()?,
+    };
+    let options = BuildOptions {
+        output: CommandOutput {
+            log_file: Some(config.command_log_path()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    hooks.run(HookStage::PostRegister, &cwd, project, &options)
+}
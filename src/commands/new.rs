@@ -1,8 +1,11 @@
 use crate::{
+    commands::{dry_run::print_dry_run, show::print_project},
     config::Config,
     database::add_project,
+    detect::detect_language,
     error::Error,
-    project::Project,
+    project::{BuildStep, Project},
+    template::{load_vars_file, BuildOptions, CommandOutput, HookStage, Verbosity},
     utils::{create_spinner, Result},
 };
 use clap::ArgMatches;
@@ -41,6 +44,8 @@ pub fn new(sub_matches: &ArgMatches, config: &mut Config) -> Result<()> {
         .collect::<Vec<_>>();
     let interactive = sub_matches.get_flag("interactive");
 
+    let mut options = build_options(sub_matches, config)?;
+
     if interactive {
         let new_params = new_params_interactive(name, desc, tags, language, category, templates)?;
 
@@ -71,27 +76,96 @@ pub fn new(sub_matches: &ArgMatches, config: &mut Config) -> Result<()> {
         config.template_dir = Some(temp_dir);
     }
 
-    let pb = create_spinner("Creating Folder...")?;
-
-    match project.build(dir, config, templates) {
-        Ok(_) => {},
-        Err(e) => match e {
-            Error::ConfigMissingValue(e) => {
+    if sub_matches.get_flag("dry-run") {
+        let rendered = match project.render(dir, config, templates, &options) {
+            Ok(rendered) => rendered,
+            Err(Error::ConfigMissingValue(e)) => {
                 println!(
                     "Missing a value for `{e}`, either set it in the config, or pass a directory through the command line"
                 );
                 return Ok(());
             },
-            e => return Err(e),
+            Err(e) => return Err(e),
+        };
+        let dir = project.directory.clone().unwrap_or_default();
+        let hooks = project.hooks(config)?;
+        return print_dry_run(&dir, &rendered, &hooks, options.policy, sub_matches.get_flag("diff"));
+    }
+
+    let pb = create_spinner("Creating Folder...")?;
+    options.output.progress = Some(pb.clone());
+
+    let built = match project.build(dir, config, templates, &options) {
+        Ok(built) => built,
+        Err(e) => {
+            pb.finish_and_clear();
+            match e {
+                Error::ConfigMissingValue(e) => {
+                    println!(
+                        "Missing a value for `{e}`, either set it in the config, or pass a directory through the command line"
+                    );
+                    return Ok(());
+                },
+                e => return Err(e),
+            }
         },
     };
     pb.finish_with_message("Folder Created");
 
-    add_project(config, &project)?;
-    println!("{project:#?}");
+    if project.language.is_none() {
+        project.language = project.directory.as_deref().and_then(detect_language);
+    }
+
+    match add_project(config, &project) {
+        Ok(rowid) => project.rowid = Some(rowid),
+        Err(e) => {
+            // A project that isn't in the database can't be found again, so don't leave what was built behind
+            built.remove();
+            return Err(Error::BuildFailed(BuildStep::Register, Box::new(e)));
+        },
+    }
+    print_project(&project);
+
+    let dir = project.directory.clone().unwrap_or_default();
+    project
+        .hooks(config)?
+        .run(HookStage::PostRegister, &dir, &project, &options)?;
     Ok(())
 }
 
+/// Read the template arguments shared by the commands that build templates
+pub(crate) fn build_options(sub_matches: &ArgMatches, config: &Config) -> Result<BuildOptions> {
+    let verbosity = if sub_matches.get_flag("verbose") {
+        Verbosity::Verbose
+    } else if sub_matches.get_flag("quiet") {
+        Verbosity::Quiet
+    } else {
+        Verbosity::Normal
+    };
+
+    let mut options = BuildOptions {
+        no_input: sub_matches.get_flag("no-input"),
+        output: CommandOutput {
+            verbosity,
+            log_file: Some(config.command_log_path()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    if let Some(vars_file) = sub_matches.get_one::<PathBuf>("vars-file") {
+        options.variables = load_vars_file(vars_file)?;
+    }
+    options.variables.extend(
+        sub_matches
+            .get_many::<(String, String)>("vars")
+            .into_iter()
+            .flatten()
+            .cloned(),
+    );
+
+    Ok(options)
+}
+
 fn new_params_interactive(
     name: Option<String>,
     desc: Option<String>,
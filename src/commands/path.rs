@@ -0,0 +1,15 @@
+use crate::{commands::select_project_fuzzy, config::Config, error::Error, utils::Result};
+use clap::ArgMatches;
+
+pub fn path(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
+    let query = sub_matches.get_one::<String>("query").cloned().unwrap();
+
+    let project = select_project_fuzzy(config, &query)?;
+
+    match project.directory {
+        Some(dir) => println!("{}", dir.display()),
+        None => return Err(Error::ProjectMissingDirectory(project.name.unwrap_or(query))),
+    }
+
+    Ok(())
+}
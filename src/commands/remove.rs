@@ -0,0 +1,42 @@
+use crate::{
+    commands::select_project,
+    config::Config,
+    database,
+    utils::{move_to_trash, Result},
+};
+use clap::ArgMatches;
+use dialoguer::Confirm;
+
+pub fn remove(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
+    let query = sub_matches.get_one::<String>("project").cloned().unwrap();
+    let trash = sub_matches.get_flag("trash");
+    let force = sub_matches.get_flag("force");
+
+    let project = select_project(config, &query)?;
+    let name = project.name.clone().unwrap_or_default();
+
+    let prompt = if trash {
+        format!("Are you sure you want to remove `{name}` and move its directory to the trash?")
+    } else {
+        format!("Are you sure you want to remove `{name}` from the database?")
+    };
+
+    if !(force || Confirm::new().with_prompt(prompt).interact()?) {
+        return Ok(());
+    }
+
+    if trash {
+        match &project.directory {
+            Some(dir) if dir.exists() => {
+                let dest = move_to_trash(dir)?;
+                println!("Moved `{}` to `{}`", dir.display(), dest.display());
+            },
+            _ => println!("The project directory does not exist, nothing to move to the trash"),
+        }
+    }
+
+    database::remove_project(config, &project)?;
+    println!("Removed `{name}` from the database");
+
+    Ok(())
+}
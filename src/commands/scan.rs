@@ -0,0 +1,200 @@
+use crate::{
+    commands::run_post_register,
+    config::Config,
+    database::{add_project, load_projects},
+    detect::{detect_language, SKIPPED_DIRS},
+    error::Error,
+    project::Project,
+    utils::Result,
+};
+use clap::ArgMatches;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::MultiSelect;
+use fs_err as fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Files and folders that mark a directory as the root of a project
+const PROJECT_MARKERS: [&str; 9] = [
+    ".git",
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "setup.py",
+    "go.mod",
+    "pom.xml",
+    "build.gradle",
+    "CMakeLists.txt",
+];
+
+pub fn scan(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
+    let root = match sub_matches.get_one::<PathBuf>("directory") {
+        Some(dir) => dir.clone(),
+        None => match &config.base_dir {
+            Some(base_dir) => base_dir.clone(),
+            None => return Err(Error::ConfigMissingValue("base_dir".to_owned())),
+        },
+    };
+    let depth = *sub_matches.get_one::<usize>("depth").unwrap();
+    let yes = sub_matches.get_flag("yes");
+
+    let existing = load_projects(config)?
+        .into_iter()
+        .filter_map(|project| project.directory)
+        .map(|dir| fs::canonicalize(&dir).unwrap_or(dir))
+        .collect::<Vec<_>>();
+
+    let projects = find_project_roots(&root, depth)?
+        .into_iter()
+        .filter(|dir| !existing.contains(&fs::canonicalize(dir).unwrap_or_else(|_| dir.clone())))
+        .map(|dir| infer_project(&root, &dir))
+        .collect::<Vec<_>>();
+
+    if projects.is_empty() {
+        println!("No new projects found in `{}`", root.display());
+        return Ok(());
+    }
+
+    let items = projects
+        .iter()
+        .map(|project| {
+            format!(
+                "{} [{}] ({})",
+                project.name.as_deref().unwrap_or_default(),
+                [project.category.as_deref(), project.language.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                project.directory.as_ref().unwrap().display()
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let selected = if yes {
+        (0..projects.len()).collect()
+    } else {
+        MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select the projects to add (space to toggle, enter to confirm)")
+            .items(&items)
+            .defaults(&vec![true; items.len()])
+            .interact()?
+    };
+
+    for index in &selected {
+        let mut project = projects[*index].clone();
+        project.rowid = Some(add_project(config, &project)?);
+        run_post_register(config, &project)?;
+    }
+    println!("Added {} project(s)", selected.len());
+
+    Ok(())
+}
+
+/// Recursively search `dir` for project roots, without descending into a project once it is found
+fn find_project_roots(dir: &Path, depth: usize) -> Result<Vec<PathBuf>> {
+    if PROJECT_MARKERS.iter().any(|marker| dir.join(marker).exists()) {
+        return Ok(vec![dir.to_path_buf()]);
+    }
+    if depth == 0 {
+        return Ok(vec![]);
+    }
+
+    let mut roots = Vec::new();
+    let mut entries = fs::read_dir(dir)?.filter_map(|entry| entry.ok()).collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !path.is_dir() || name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_str()) {
+            continue;
+        }
+        roots.extend(find_project_roots(&path, depth - 1)?);
+    }
+
+    Ok(roots)
+}
+
+/// Build a project from its directory, using the `<base>/<category>/<language>/<name>` layout
+/// that `Config::gen_project_folder` creates to fill in the category and language, and
+/// detecting the language when the layout doesn't include it
+fn infer_project(root: &Path, dir: &Path) -> Project {
+    let components = dir
+        .strip_prefix(root)
+        .unwrap_or(dir)
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    // A relative path would only lead back to the project from where the scan was run
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let name = dir.file_name().map(|name| name.to_string_lossy().to_string());
+    let detected = detect_language(&dir);
+    let (category, language) = match components.len() {
+        0 | 1 => (None, detected),
+        // A single parent folder is either the language or the category
+        2 => match detected {
+            Some(language) if !language.eq_ignore_ascii_case(&components[0]) => (Some(components[0].clone()), Some(language)),
+            _ => (None, Some(components[0].clone())),
+        },
+        len => (Some(components[len - 3].clone()), Some(components[len - 2].clone())),
+    };
+
+    let mut project = Project::new(name, None, vec![], language, category);
+    project.directory = Some(dir);
+    project
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_find_project_roots() -> Result<()> {
+        let root = Path::new("tests/test_files/scan");
+        if root.exists() {
+            fs::remove_dir_all(root)?;
+        }
+        fs::create_dir_all(root.join("work/rust/app/src"))?;
+        fs::write(root.join("work/rust/app/Cargo.toml"), "")?;
+        fs::write(root.join("work/rust/app/src/Cargo.toml"), "")?;
+        fs::create_dir_all(root.join("python/tool/.git"))?;
+        fs::create_dir_all(root.join("tools/helper"))?;
+        fs::write(root.join("tools/helper/go.mod"), "")?;
+        fs::create_dir_all(root.join("empty/folder"))?;
+        fs::create_dir_all(root.join("node_modules/package"))?;
+        fs::write(root.join("node_modules/package/package.json"), "")?;
+
+        let roots = find_project_roots(root, 5)?;
+        assert_eq!(
+            roots,
+            vec![
+                root.join("python/tool"),
+                root.join("tools/helper"),
+                root.join("work/rust/app")
+            ]
+        );
+
+        let project = infer_project(root, &roots[2]);
+        assert_eq!(project.name, Some("app".to_owned()));
+        assert_eq!(project.language, Some("rust".to_owned()));
+        assert_eq!(project.category, Some("work".to_owned()));
+        assert_eq!(project.directory, Some(fs::canonicalize(&roots[2])?));
+        assert!(project.directory.unwrap().is_absolute());
+
+        let project = infer_project(root, &roots[0]);
+        assert_eq!(project.name, Some("tool".to_owned()));
+        assert_eq!(project.language, Some("python".to_owned()));
+        assert_eq!(project.category, None);
+
+        let project = infer_project(root, &roots[1]);
+        assert_eq!(project.language, Some("go".to_owned()));
+        assert_eq!(project.category, Some("tools".to_owned()));
+
+        Ok(())
+    }
+}
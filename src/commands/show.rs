@@ -0,0 +1,185 @@
+use crate::{commands::select_project_fuzzy, config::Config, project::Project, utils::Result};
+use chrono::{DateTime, Local};
+use clap::ArgMatches;
+use console::style;
+use fs_err as fs;
+use indicatif::HumanBytes;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+#[derive(Debug, Serialize)]
+struct ProjectDetails {
+    rowid: Option<i64>,
+    name: Option<String>,
+    desc: Option<String>,
+    directory: Option<PathBuf>,
+    tags: Vec<String>,
+    language: Option<String>,
+    category: Option<String>,
+    templates: Vec<String>,
+    archived: bool,
+    exists: bool,
+    git: Option<GitStatus>,
+    last_modified: Option<String>,
+    size: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct GitStatus {
+    branch: String,
+    dirty: bool,
+}
+
+impl ProjectDetails {
+    /// The stored fields of the project, with the state of its directory when `inspect` is set
+    ///
+    /// Inspecting walks the whole directory and runs git, which is slow for large projects.
+    fn new(project: &Project, inspect: bool) -> Self {
+        let dir = project.directory.as_ref().filter(|dir| dir.is_dir());
+        let inspected = dir.filter(|_| inspect);
+
+        let (size, modified) = match inspected {
+            Some(dir) => dir_stats(dir),
+            None => (0, None),
+        };
+
+        ProjectDetails {
+            rowid: project.rowid,
+            name: project.name.clone(),
+            desc: project.desc.clone(),
+            directory: project.directory.clone(),
+            tags: project.tags.clone().unwrap_or_default(),
+            language: project.language.clone(),
+            category: project.category.clone(),
+            templates: project.templates.clone().unwrap_or_default(),
+            archived: project.archived.unwrap_or_default(),
+            exists: dir.is_some(),
+            git: inspected.and_then(|dir| git_status(dir)),
+            last_modified: modified.map(|time| DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S %:z").to_string()),
+            size: inspected.map(|_| size),
+        }
+    }
+}
+
+pub fn show(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
+    let query = sub_matches.get_one::<String>("project").cloned().unwrap();
+    let format = sub_matches.get_one::<String>("format").cloned().unwrap_or_default();
+
+    let project = select_project_fuzzy(config, &query)?;
+
+    let details = ProjectDetails::new(&project, true);
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&details)?);
+    } else {
+        print_details(&details);
+    }
+
+    Ok(())
+}
+
+/// Print every field of a project and whether its directory exists
+pub fn print_project(project: &Project) {
+    print_details(&ProjectDetails::new(project, false));
+}
+
+fn print_details(details: &ProjectDetails) {
+    let or_none = |value: Option<String>| value.unwrap_or_else(|| "-".to_owned());
+    let join = |values: &[String]| {
+        if values.is_empty() {
+            "-".to_owned()
+        } else {
+            values.join(", ")
+        }
+    };
+
+    let mut lines = vec![
+        ("Name", or_none(details.name.clone())),
+        ("ID", or_none(details.rowid.map(|id| id.to_string()))),
+        ("Description", or_none(details.desc.clone())),
+        (
+            "Directory",
+            or_none(details.directory.as_ref().map(|dir| dir.display().to_string())),
+        ),
+        ("Tags", join(&details.tags)),
+        ("Language", or_none(details.language.clone())),
+        ("Category", or_none(details.category.clone())),
+        ("Templates", join(&details.templates)),
+        ("Archived", if details.archived { "yes" } else { "no" }.to_owned()),
+        ("Exists", if details.exists { "yes" } else { "no" }.to_owned()),
+    ];
+
+    if details.exists && details.size.is_some() {
+        lines.push((
+            "Git",
+            match &details.git {
+                Some(git) if git.dirty => format!("{} (dirty)", git.branch),
+                Some(git) => format!("{} (clean)", git.branch),
+                None => "-".to_owned(),
+            },
+        ));
+        lines.push(("Last Modified", or_none(details.last_modified.clone())));
+        lines.push(("Size", HumanBytes(details.size.unwrap_or_default()).to_string()));
+    }
+
+    let width = lines.iter().map(|(key, _)| key.len()).max().unwrap_or_default() + 1;
+    for (key, value) in lines {
+        println!("{} {value}", style(format!("{key:<width$}", key = format!("{key}:"))).bold());
+    }
+}
+
+fn git_status(dir: &Path) -> Option<GitStatus> {
+    let branch = Command::new("git")
+        .args(["branch", "--show-current"])
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let status = Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    let branch = String::from_utf8_lossy(&branch.stdout).trim().to_owned();
+
+    Some(GitStatus {
+        branch: if branch.is_empty() {
+            "HEAD (detached)".to_owned()
+        } else {
+            branch
+        },
+        dirty: !status.stdout.is_empty(),
+    })
+}
+
+/// Total size of the files in a directory and the most recent modification time
+fn dir_stats(dir: &Path) -> (u64, Option<SystemTime>) {
+    let mut size = 0;
+    let mut modified = fs::metadata(dir).and_then(|meta| meta.modified()).ok();
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return (size, modified),
+    };
+
+    for entry in entries.flatten() {
+        let meta = match fs::symlink_metadata(entry.path()) {
+            Ok(meta) => meta,
+            Err(_) => continue,
+        };
+
+        let (entry_size, entry_modified) = if meta.is_dir() {
+            dir_stats(&entry.path())
+        } else {
+            (meta.len(), meta.modified().ok())
+        };
+
+        size += entry_size;
+        modified = modified.max(entry_modified);
+    }
+
+    (size, modified)
+}
@@ -0,0 +1,234 @@
+use crate::{
+    commands::select_project,
+    config::Config,
+    error::Error,
+    template::{create_template, Template, TemplateVar},
+    utils::Result,
+};
+use clap::ArgMatches;
+use console::style;
+use prettytable::{format, row, Table};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub fn template(sub_matches: &ArgMatches, config: &Config) -> Result<()> {
+    match sub_matches.subcommand() {
+        Some(("list", _)) => list(config)?,
+        Some(("show", sub_matches)) => {
+            let name = sub_matches.get_one::<String>("name").cloned().unwrap();
+            show(&name, config)?;
+        },
+        Some(("create", sub_matches)) => {
+            let name = sub_matches.get_one::<String>("name").cloned().unwrap();
+            let from = sub_matches.get_one::<String>("from").cloned().unwrap();
+            let desc = sub_matches.get_one::<String>("desc").cloned();
+            let replacements = sub_matches
+                .get_many::<(String, String)>("replace")
+                .unwrap_or_default()
+                .cloned()
+                .collect::<Vec<_>>();
+            create(&name, &from, desc, &replacements, config)?;
+        },
+        Some(("validate", sub_matches)) => {
+            let name = sub_matches.get_one::<String>("name").cloned();
+            validate(name, config)?;
+        },
+        Some((command, _)) => {
+            println!("Code has not yet been written for `{command}`");
+        },
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+fn list(config: &Config) -> Result<()> {
+    let templates = Template::load_templates(config)?;
+    let mut names = templates.keys().collect::<Vec<_>>();
+    names.sort();
+
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BOX_CHARS);
+    table.set_titles(row!["Name", "Description"]);
+    for name in names {
+        // The description can come from the template it extends, and a broken one shouldn't hide the rest
+        let desc = match Template::resolve(name, &templates) {
+            Ok(template) => template.desc.unwrap_or_default(),
+            Err(e) => format!("Can't be used: {e}"),
+        };
+        table.add_row(row![name, desc]);
+    }
+    table.printstd();
+    Ok(())
+}
+
+fn show(name: &str, config: &Config) -> Result<()> {
+    let template = Template::resolve(name, &Template::load_templates(config)?)?;
+
+    println!("{} {}", style("Name:").bold(), template.name);
+    if let Some(desc) = &template.desc {
+        println!("{} {desc}", style("Description:").bold());
+    }
+    if let Some(extends) = &template.extends {
+        println!("{} {extends}", style("Extends:").bold());
+    }
+    if let Some(includes) = template.includes.as_ref().filter(|includes| !includes.is_empty()) {
+        println!("{} {}", style("Includes:").bold(), includes.join(", "));
+    }
+
+    println!("{}", style("Variables:").bold());
+    for var in template.template_vars.iter().flatten() {
+        println!("  - {}", describe_var(var));
+    }
+
+    println!("{}", style("Files:").bold());
+    match template.source_files(config) {
+        Ok(files) => {
+            for (_, file) in files {
+                println!("  - {}", file.display());
+            }
+        },
+        Err(e @ Error::TemplateConflict(..)) => println!("  {}", style(e).red()),
+        Err(e) => return Err(e),
+    }
+
+    println!("{}", style("Commands:").bold());
+    for command in template.commands.iter().flatten() {
+        println!("  - {command}");
+    }
+
+    if !template.hooks.is_empty() {
+        println!("{}", style("Hooks:").bold());
+        for (stage, command) in template.hooks.stages() {
+            println!("  - {stage}: {command}");
+        }
+    }
+
+    Ok(())
+}
+
+fn create(name: &str, from: &str, desc: Option<String>, replacements: &[(String, String)], config: &Config) -> Result<()> {
+    let source = match Path::new(from).is_dir() {
+        true => PathBuf::from(from),
+        false => {
+            let project = select_project(config, from)?;
+            match project.directory {
+                Some(dir) => dir,
+                None => return Err(Error::ProjectMissingDirectory(project.name.unwrap_or(from.to_owned()))),
+            }
+        },
+    };
+
+    let (template, report) = create_template(name, desc, &source, replacements, config)?;
+    for file in report.skipped {
+        println!("{} Skipped `{}`, it is not a text file", style("!").yellow(), file.display());
+    }
+    for (file, placeholders) in report.placeholders {
+        let placeholders = placeholders
+            .iter()
+            .map(|name| format!("${{{name}}}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "{} `{}` already contains {placeholders}, these will be filled in like template variables",
+            style("!").yellow(),
+            file.display()
+        );
+    }
+    println!("Created the template `{}` from `{}`", template.name, source.display());
+
+    Ok(())
+}
+
+fn validate(name: Option<String>, config: &Config) -> Result<()> {
+    let mut problem_count = 0;
+    let mut seen: HashMap<String, PathBuf> = HashMap::new();
+
+    let mut loaded = Vec::new();
+    for path in Template::template_paths(config)? {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        match Template::load(&path) {
+            Ok(template) => loaded.push((path, file_name, template)),
+            Err(e) => {
+                if name.is_none() {
+                    report(&file_name, &[format!("unable to load the template: {e}")]);
+                    problem_count += 1;
+                }
+            },
+        }
+    }
+
+    // Templates extend and include each other by name, the first file with a name is the one that's used
+    let mut raw: HashMap<String, Template> = HashMap::new();
+    for (_, _, template) in &loaded {
+        raw.entry(template.name.clone()).or_insert_with(|| template.clone());
+    }
+
+    for (path, file_name, template) in loaded {
+        if name.as_ref().is_some_and(|name| *name != template.name) {
+            continue;
+        }
+
+        // A template shadowed by another with the same name is never resolved, so only its own files are checked
+        let mut problems = match seen.contains_key(&template.name) {
+            true => template.validate(config)?,
+            false => match Template::resolve(&template.name, &raw) {
+                Ok(resolved) => resolved.validate(config)?,
+                Err(e) => vec![format!("unable to resolve the template: {e}")],
+            },
+        };
+        if let Some(first) = seen.get(&template.name) {
+            problems.push(format!(
+                "the name `{}` is already used by `{}`",
+                template.name,
+                first.file_name().unwrap_or_default().to_string_lossy()
+            ));
+        } else {
+            seen.insert(template.name.clone(), path.clone());
+        }
+
+        report(&format!("{} ({file_name})", template.name), &problems);
+        problem_count += problems.len();
+    }
+
+    if let Some(name) = name {
+        if seen.is_empty() {
+            return Err(Error::TemplateNotFound(name));
+        }
+    }
+
+    if problem_count > 0 {
+        return Err(Error::TemplateValidationFailed(problem_count));
+    }
+
+    Ok(())
+}
+
+fn report(name: &str, problems: &[String]) {
+    if problems.is_empty() {
+        println!("{} {name}", style("✔").green());
+    } else {
+        println!("{} {name}", style("✘").red());
+        for problem in problems {
+            println!("    - {problem}");
+        }
+    }
+}
+
+fn describe_var(var: &TemplateVar) -> String {
+    let spec = var.spec();
+    let kind = format!("{:?}", spec.kind).to_lowercase();
+    let mut description = format!("{} ({kind})", spec.name);
+
+    if let Some(choices) = &spec.choices {
+        description.push_str(&format!(" [{}]", choices.join(", ")));
+    }
+    if let Some(default) = &spec.default {
+        description.push_str(&format!(" default: {default}"));
+    }
+    if let Some(help) = &spec.help {
+        description.push_str(&format!(" - {help}"));
+    }
+
+    description
+}
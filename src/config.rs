@@ -1,19 +1,25 @@
 use crate::error::Error;
 use crate::project::Project;
+use crate::template::Hooks;
 use crate::utils::{config_folder, Result};
 use fs_err as fs;
 use serde::{Deserialize, Serialize};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub const CONFIG_FILE: &str = "ProjectOrganizer.toml";
 pub const DATABASE_FILE: &str = "projectDB.db";
+pub const COMMAND_LOG_FILE: &str = "commands.log";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub database_path: String,
     pub base_dir: Option<PathBuf>,
     pub template_dir: Option<PathBuf>,
+    /// Columns shown by `list` when none are passed on the command line
+    pub list_columns: Option<Vec<String>>,
+    /// Hooks run for every project, before the hooks of its templates
+    pub hooks: Option<Hooks>,
 }
 
 impl Default for Config {
@@ -33,6 +39,8 @@ impl Config {
             database_path: db_path.to_str().unwrap_or_default().to_owned(),
             base_dir: None,
             template_dir: None,
+            list_columns: None,
+            hooks: None,
         }
     }
 
@@ -65,6 +73,11 @@ impl Config {
         Ok(())
     }
 
+    /// The log of every template command, kept next to the database
+    pub fn command_log_path(&self) -> PathBuf {
+        Path::new(&self.database_path).with_file_name(COMMAND_LOG_FILE)
+    }
+
     pub fn gen_project_folder(&self, project: &Project) -> Result<PathBuf> {
         let mut path = match &self.base_dir {
             Some(base_dir) => PathBuf::from(base_dir),
@@ -1,8 +1,8 @@
-use crate::{config::Config, project::Project, utils};
+use crate::{config::Config, error::Error, project::Project, utils};
 use fs_err as fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Once;
-use turbosql::{select, set_db_path, Turbosql};
+use turbosql::{execute, select, set_db_path, Turbosql};
 
 static DB_INIT: Once = Once::new();
 
@@ -23,14 +23,128 @@ fn set_db(config: &Config) -> utils::Result<()> {
     Ok(())
 }
 
-pub fn add_project(config: &Config, project: &Project) -> utils::Result<()> {
+/// Insert the project, returning its id
+pub fn add_project(config: &Config, project: &Project) -> utils::Result<i64> {
     set_db(config)?;
 
-    project.insert()?;
+    Ok(project.insert()?)
+}
+
+/// Insert the projects in one transaction, first removing every existing project when `replace` is set
+///
+/// If any insert fails, the database is left as it was.
+pub fn import_projects(config: &Config, projects: &[Project], replace: bool) -> utils::Result<Vec<i64>> {
+    set_db(config)?;
+
+    execute!("BEGIN TRANSACTION")?;
+    let result = (|| {
+        if replace {
+            execute!("DELETE FROM project")?;
+        }
+        projects
+            .iter()
+            .map(|project| Ok(project.insert()?))
+            .collect::<utils::Result<Vec<_>>>()
+    })();
+
+    match result {
+        Ok(rowids) => {
+            execute!("COMMIT")?;
+            Ok(rowids)
+        },
+        Err(e) => {
+            let _ = execute!("ROLLBACK");
+            Err(e)
+        },
+    }
+}
+
+pub fn update_project(config: &Config, project: &Project) -> utils::Result<()> {
+    set_db(config)?;
+
+    project.update()?;
 
     Ok(())
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Language,
+    Category,
+    #[default]
+    Rowid,
+}
+
+impl SortKey {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Language => "language",
+            SortKey::Category => "category",
+            SortKey::Rowid => "rowid",
+        }
+    }
+}
+
+/// Filters applied by `query_projects`, every field left empty matches all projects
+#[derive(Debug, Default, Clone)]
+pub struct ProjectFilter {
+    /// Projects must have every one of these tags
+    pub tags: Vec<String>,
+    pub language: Option<String>,
+    pub category: Option<String>,
+    /// Glob pattern matched against the project name
+    pub name: Option<String>,
+    /// Free text searched for in the name, description, directory and tags
+    pub search: Option<String>,
+    pub sort: SortKey,
+    pub reverse: bool,
+    /// Include projects that have been archived
+    pub archived: bool,
+}
+
+pub fn query_projects(config: &Config, filter: &ProjectFilter) -> utils::Result<Vec<Project>> {
+    set_db(config)?;
+
+    // Tags are stored as a JSON array, so they are matched with `json_each`
+    let tags = serde_json::to_string(&filter.tags)?;
+    // The search is plain text, so the wildcards of `LIKE` are escaped
+    let search = filter.search.as_ref().map(|search| {
+        let escaped = search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        format!("%{escaped}%")
+    });
+    let sort = filter.sort.as_str();
+    let reverse = filter.reverse as i64;
+    let archived = filter.archived as i64;
+
+    let projects = select!(Vec<Project> "WHERE (? IS NULL OR language = ? COLLATE NOCASE) \
+        AND (? IS NULL OR category = ? COLLATE NOCASE) \
+        AND (? IS NULL OR name GLOB ?) \
+        AND (? IS NULL OR name LIKE ? ESCAPE '\\' OR desc LIKE ? ESCAPE '\\' \
+            OR directory LIKE ? ESCAPE '\\' OR tags LIKE ? ESCAPE '\\') \
+        AND NOT EXISTS (SELECT 1 FROM json_each(?) AS wanted WHERE NOT EXISTS \
+            (SELECT 1 FROM json_each(project.tags) AS tag WHERE tag.value = wanted.value COLLATE NOCASE)) \
+        AND (? = 1 OR archived IS NOT 1) \
+        ORDER BY \
+            CASE WHEN ? = 0 THEN (CASE ? WHEN 'name' THEN name WHEN 'language' THEN language \
+                WHEN 'category' THEN category ELSE rowid END) END COLLATE NOCASE ASC, \
+            CASE WHEN ? = 1 THEN (CASE ? WHEN 'name' THEN name WHEN 'language' THEN language \
+                WHEN 'category' THEN category ELSE rowid END) END COLLATE NOCASE DESC, \
+            rowid",
+        filter.language, filter.language,
+        filter.category, filter.category,
+        filter.name, filter.name,
+        search, search, search, search, search,
+        tags,
+        archived,
+        reverse, sort,
+        reverse, sort
+    )?;
+
+    Ok(projects)
+}
+
 pub fn load_projects(config: &Config) -> utils::Result<Vec<Project>> {
     set_db(config)?;
 
@@ -42,6 +156,53 @@ pub fn load_projects(config: &Config) -> utils::Result<Vec<Project>> {
     }
 }
 
+/// Find every project whose id, name, or directory matches `query`
+pub fn find_projects(config: &Config, query: &str) -> utils::Result<Vec<Project>> {
+    set_db(config)?;
+
+    if let Ok(rowid) = query.parse::<i64>() {
+        let projects = select!(Vec<Project> "WHERE rowid = " rowid)?;
+        if !projects.is_empty() {
+            return Ok(projects);
+        }
+    }
+
+    let projects = select!(Vec<Project> "WHERE name = " query)?;
+    if !projects.is_empty() {
+        return Ok(projects);
+    }
+
+    // Directories are stored serialized, so compare against the serialized form of the path
+    let mut directories = vec![serde_json::to_string(Path::new(query))?];
+    if let Ok(canonical) = fs::canonicalize(query) {
+        directories.push(serde_json::to_string(&canonical)?);
+    }
+
+    let mut projects = Vec::new();
+    for directory in directories {
+        for project in select!(Vec<Project> "WHERE directory = " directory)? {
+            if !projects.contains(&project) {
+                projects.push(project);
+            }
+        }
+    }
+
+    Ok(projects)
+}
+
+pub fn remove_project(config: &Config, project: &Project) -> utils::Result<()> {
+    set_db(config)?;
+
+    let rowid = match project.rowid {
+        Some(rowid) => rowid,
+        None => return Err(Error::ProjectNotFound(project.name.clone().unwrap_or_default())),
+    };
+
+    execute!("DELETE FROM project WHERE rowid = " rowid)?;
+
+    Ok(())
+}
+
 pub fn reset_database(config: &Config) -> utils::Result<()> {
     fs::remove_file(&config.database_path)?;
     Ok(())
@@ -52,16 +213,44 @@ mod tests {
     use super::*;
     use crate::config::Config;
     use anyhow::Result;
+    use std::sync::{Mutex, MutexGuard};
+
+    // Every test shares the same database file, so they have to run one at a time
+    static DB_LOCK: Mutex<()> = Mutex::new(());
+
+    fn setup() -> Result<(Config, MutexGuard<'static, ()>)> {
+        let guard = DB_LOCK.lock().unwrap_or_else(|e| e.into_inner());
 
-    #[test]
-    fn test_add() -> Result<()> {
         let config = Config {
             database_path: "./tests/test_files/database.db".to_owned(),
             base_dir: None,
             template_dir: None,
+            list_columns: None,
+            hooks: None,
         };
 
-        let _ = std::fs::remove_file(&config.database_path);
+        clear_projects(&config)?;
+
+        Ok((config, guard))
+    }
+
+    fn clear_projects(config: &Config) -> Result<()> {
+        set_db(config)?;
+        execute!("DELETE FROM project")?;
+        Ok(())
+    }
+
+    fn test_project(name: &str, directory: &str) -> Project {
+        Project {
+            name: Some(name.to_owned()),
+            directory: Some(PathBuf::from(directory)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_add() -> Result<()> {
+        let (config, _guard) = setup()?;
 
         let project = Project {
             rowid: None,
@@ -71,6 +260,8 @@ mod tests {
             tags: Some(vec!["test".to_owned(), "project".to_owned()]),
             language: Some("rust".to_owned()),
             category: None,
+            templates: None,
+            archived: None,
         };
 
         println!("Adding project");
@@ -92,4 +283,184 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_find_projects() -> Result<()> {
+        let (config, _guard) = setup()?;
+
+        add_project(&config, &test_project("first", "/dev/first"))?;
+        add_project(&config, &test_project("second", "/dev/second"))?;
+        add_project(&config, &test_project("second", "/dev/other/second"))?;
+
+        let by_name = find_projects(&config, "first")?;
+        assert_eq!(by_name.len(), 1);
+        assert_eq!(by_name[0].directory, Some(PathBuf::from("/dev/first")));
+
+        let by_id = find_projects(&config, &by_name[0].rowid.unwrap().to_string())?;
+        assert_eq!(by_id, by_name);
+
+        let by_dir = find_projects(&config, "/dev/other/second")?;
+        assert_eq!(by_dir.len(), 1);
+        assert_eq!(by_dir[0].name, Some("second".to_owned()));
+
+        assert_eq!(find_projects(&config, "second")?.len(), 2);
+        assert!(find_projects(&config, "missing")?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update() -> Result<()> {
+        let (config, _guard) = setup()?;
+
+        add_project(&config, &test_project("old", "/dev/old"))?;
+
+        let mut project = find_projects(&config, "old")?.remove(0);
+        project.name = Some("new".to_owned());
+        project.tags = Some(vec!["edited".to_owned()]);
+        update_project(&config, &project)?;
+
+        let projects = load_projects(&config)?;
+        assert_eq!(projects, vec![project]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_query() -> Result<()> {
+        let (config, _guard) = setup()?;
+
+        let projects = [
+            ("alpha", "rust", vec!["cli", "work"]),
+            ("beta", "python", vec!["cli"]),
+            ("gamma", "Rust", vec![]),
+        ];
+        for (name, language, tags) in projects {
+            add_project(
+                &config,
+                &Project {
+                    language: Some(language.to_owned()),
+                    tags: Some(tags.into_iter().map(str::to_owned).collect()),
+                    ..test_project(name, &format!("/dev/{name}"))
+                },
+            )?;
+        }
+
+        let names = |filter: ProjectFilter| -> Result<Vec<String>> {
+            Ok(query_projects(&config, &filter)?
+                .into_iter()
+                .map(|p| p.name.unwrap_or_default())
+                .collect())
+        };
+
+        let mut archived = query_projects(&config, &ProjectFilter::default())?.remove(1);
+        archived.archived = Some(true);
+        update_project(&config, &archived)?;
+
+        assert_eq!(names(ProjectFilter::default())?, vec!["alpha", "gamma"]);
+        assert_eq!(
+            names(ProjectFilter {
+                archived: true,
+                ..Default::default()
+            })?,
+            vec!["alpha", "beta", "gamma"]
+        );
+        assert_eq!(
+            names(ProjectFilter {
+                language: Some("rust".to_owned()),
+                ..Default::default()
+            })?,
+            vec!["alpha", "gamma"]
+        );
+        assert_eq!(
+            names(ProjectFilter {
+                tags: vec!["cli".to_owned(), "work".to_owned()],
+                ..Default::default()
+            })?,
+            vec!["alpha"]
+        );
+        assert_eq!(
+            names(ProjectFilter {
+                name: Some("*a".to_owned()),
+                sort: SortKey::Name,
+                reverse: true,
+                archived: true,
+                ..Default::default()
+            })?,
+            vec!["gamma", "beta", "alpha"]
+        );
+        assert_eq!(
+            names(ProjectFilter {
+                search: Some("work".to_owned()),
+                ..Default::default()
+            })?,
+            vec!["alpha"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_is_literal() -> Result<()> {
+        let (config, _guard) = setup()?;
+
+        for name in ["my_app", "myXapp", "100%", "1000"] {
+            add_project(&config, &test_project(name, &format!("/dev/{name}")))?;
+        }
+
+        let search = |search: &str| -> Result<Vec<String>> {
+            let filter = ProjectFilter {
+                search: Some(search.to_owned()),
+                ..Default::default()
+            };
+            Ok(query_projects(&config, &filter)?
+                .into_iter()
+                .map(|p| p.name.unwrap_or_default())
+                .collect())
+        };
+
+        assert_eq!(search("my_app")?, vec!["my_app"]);
+        assert_eq!(search("0%")?, vec!["100%"]);
+        assert_eq!(search("\\")?, Vec::<String>::new());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove() -> Result<()> {
+        let (config, _guard) = setup()?;
+
+        add_project(&config, &test_project("keep", "/dev/keep"))?;
+        add_project(&config, &test_project("remove", "/dev/remove"))?;
+
+        let project = find_projects(&config, "remove")?.remove(0);
+        remove_project(&config, &project)?;
+
+        let projects = load_projects(&config)?;
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, Some("keep".to_owned()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_projects() -> Result<()> {
+        let (config, _guard) = setup()?;
+
+        add_project(&config, &test_project("old", "/dev/old"))?;
+        let imported = [test_project("demo", "/dev/demo"), test_project("demo", "/dev/other/demo")];
+
+        let rowids = import_projects(&config, &imported, false)?;
+        assert_eq!(rowids.len(), 2);
+        assert_eq!(load_projects(&config)?.len(), 3);
+
+        import_projects(&config, &imported, true)?;
+        let names = load_projects(&config)?
+            .into_iter()
+            .filter_map(|project| project.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["demo", "demo"]);
+
+        Ok(())
+    }
 }
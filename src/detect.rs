@@ -0,0 +1,192 @@
+use fs_err as fs;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Folders that hold dependencies or build output rather than the project's own source
+pub const SKIPPED_DIRS: [&str; 4] = ["node_modules", "target", "vendor", "venv"];
+
+/// Manifest files that identify the language of a project, checked in order
+const MANIFESTS: [(&str, &str); 16] = [
+    ("Cargo.toml", "rust"),
+    ("go.mod", "go"),
+    ("pyproject.toml", "python"),
+    ("setup.py", "python"),
+    ("requirements.txt", "python"),
+    ("Pipfile", "python"),
+    ("build.gradle.kts", "kotlin"),
+    ("pom.xml", "java"),
+    ("build.gradle", "java"),
+    ("Gemfile", "ruby"),
+    ("composer.json", "php"),
+    ("mix.exs", "elixir"),
+    ("pubspec.yaml", "dart"),
+    ("Package.swift", "swift"),
+    ("build.zig", "zig"),
+    ("stack.yaml", "haskell"),
+];
+
+const EXTENSIONS: [(&str, &str); 24] = [
+    ("rs", "rust"),
+    ("py", "python"),
+    ("js", "javascript"),
+    ("jsx", "javascript"),
+    ("mjs", "javascript"),
+    ("ts", "typescript"),
+    ("tsx", "typescript"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("rb", "ruby"),
+    ("php", "php"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cpp", "cpp"),
+    ("cc", "cpp"),
+    ("hpp", "cpp"),
+    ("cs", "csharp"),
+    ("swift", "swift"),
+    ("lua", "lua"),
+    ("ex", "elixir"),
+    ("dart", "dart"),
+    ("zig", "zig"),
+    ("hs", "haskell"),
+];
+
+/// The most files looked at when falling back to counting extensions
+const MAX_FILES: usize = 10_000;
+
+/// Detect the primary language of the project in `dir`, first from its manifest files and then
+/// by counting the extensions of the files it contains
+pub fn detect_language(dir: &Path) -> Option<String> {
+    if !dir.is_dir() {
+        return None;
+    }
+
+    detect_from_manifest(dir).or_else(|| detect_from_extensions(dir))
+}
+
+fn detect_from_manifest(dir: &Path) -> Option<String> {
+    if dir.join("package.json").exists() {
+        let uses_typescript = dir.join("tsconfig.json").exists()
+            || fs::read_to_string(dir.join("package.json"))
+                .map(|contents| contents.contains("\"typescript\""))
+                .unwrap_or_default();
+        return Some(if uses_typescript { "typescript" } else { "javascript" }.to_owned());
+    }
+
+    if let Some((_, language)) = MANIFESTS.iter().find(|(manifest, _)| dir.join(manifest).exists()) {
+        return Some(language.to_string());
+    }
+
+    // .NET projects name their manifest after the project
+    let entries = fs::read_dir(dir).ok()?;
+    for entry in entries.flatten() {
+        match entry.path().extension().and_then(|ext| ext.to_str()) {
+            Some("csproj") | Some("sln") => return Some("csharp".to_owned()),
+            Some("fsproj") => return Some("fsharp".to_owned()),
+            _ => {},
+        }
+    }
+
+    None
+}
+
+fn detect_from_extensions(dir: &Path) -> Option<String> {
+    let mut counts = HashMap::new();
+    let mut remaining = MAX_FILES;
+    count_extensions(dir, &mut counts, &mut remaining);
+
+    counts
+        .into_iter()
+        .max_by(|(a_lang, a_count), (b_lang, b_count)| a_count.cmp(b_count).then(b_lang.cmp(a_lang)))
+        .map(|(language, _)| language.to_owned())
+}
+
+fn count_extensions(dir: &Path, counts: &mut HashMap<&'static str, usize>, remaining: &mut usize) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        if *remaining == 0 {
+            return;
+        }
+
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+
+        if path.is_dir() {
+            if !SKIPPED_DIRS.contains(&name.as_str()) {
+                count_extensions(&path, counts, remaining);
+            }
+        } else if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+            *remaining -= 1;
+            if let Some((_, language)) = EXTENSIONS.iter().find(|(e, _)| e.eq_ignore_ascii_case(ext)) {
+                *counts.entry(*language).or_default() += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::path::PathBuf;
+
+    fn test_dir(name: &str, files: &[&str]) -> Result<PathBuf> {
+        let dir = Path::new("tests/test_files/detect").join(name);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        for file in files {
+            let file = dir.join(file);
+            fs::create_dir_all(file.parent().unwrap())?;
+            fs::write(file, "")?;
+        }
+        Ok(dir)
+    }
+
+    #[test]
+    fn test_detect_manifest() -> Result<()> {
+        assert_eq!(
+            detect_language(&test_dir("rust", &["Cargo.toml", "main.py"])?),
+            Some("rust".to_owned())
+        );
+        assert_eq!(
+            detect_language(&test_dir("js", &["package.json"])?),
+            Some("javascript".to_owned())
+        );
+        assert_eq!(
+            detect_language(&test_dir("ts", &["package.json", "tsconfig.json"])?),
+            Some("typescript".to_owned())
+        );
+        assert_eq!(
+            detect_language(&test_dir("dotnet", &["App.csproj"])?),
+            Some("csharp".to_owned())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_extensions() -> Result<()> {
+        let dir = test_dir(
+            "extensions",
+            &[
+                "src/main.c",
+                "src/util.c",
+                "src/util.h",
+                "scripts/build.py",
+                "node_modules/a/b.js",
+                "c.js",
+            ],
+        )?;
+        assert_eq!(detect_language(&dir), Some("c".to_owned()));
+        assert_eq!(detect_language(&test_dir("empty", &["README.md"])?), None);
+        Ok(())
+    }
+}
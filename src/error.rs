@@ -1,3 +1,7 @@
+use crate::project::BuildStep;
+use crate::template::HookStage;
+use std::path::PathBuf;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error(transparent)]
@@ -12,6 +16,9 @@ pub enum Error {
     #[error(transparent)]
     TomlSer(#[from] toml::ser::Error),
 
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+
     #[error(transparent)]
     SerdeYaml(#[from] serde_yaml::Error),
 
@@ -24,12 +31,72 @@ pub enum Error {
     #[error("key `{0}` is invalid for the config")]
     ConfigInvalidKey(String),
 
+    #[error("`{0}` is not a valid column, expected one of: name, desc, tags, language, category, directory, rowid")]
+    InvalidColumn(String),
+
+    #[error("Unable to tell the format of `{0}`, specify it with `--format`")]
+    UnknownFormat(String),
+
     #[error("The template `{0}` was not found in the template directory")]
     TemplateNotFound(String),
 
+    #[error("No project matching `{0}` was found in the database")]
+    ProjectNotFound(String),
+
+    #[error("More than one project matches `{0}`, use the project id instead")]
+    ProjectAmbiguous(String),
+
+    #[error("The project `{0}` does not have a directory")]
+    ProjectMissingDirectory(String),
+
+    #[error("The directory `{}` already exists and is not empty", .0.display())]
+    DirectoryNotEmpty(PathBuf),
+
+    #[error("Creating the project failed while {0}, nothing was left behind: {1}")]
+    BuildFailed(BuildStep, Box<Error>),
+
+    #[error("The template `{0}` is defined in both `{}` and `{}`", .1.display(), .2.display())]
+    DuplicateTemplate(String, PathBuf, PathBuf),
+
+    #[error("A template named `{0}` already exists")]
+    TemplateExists(String),
+
+    #[error("`{0}` is not a valid template name")]
+    InvalidTemplateName(String),
+
+    #[error(transparent)]
+    Ignore(#[from] ignore::Error),
+
+    #[error("The templates `{0}` extend or include each other")]
+    TemplateCycle(String),
+
+    #[error("`{0}` is written by both the `{1}` and `{2}` templates")]
+    TemplateConflict(String, String, String),
+
+    #[error("Found {0} problem(s) with the templates")]
+    TemplateValidationFailed(usize),
+
+    #[error("invalid value for the template variable `{0}`: {1}")]
+    InvalidTemplateVariable(String, String),
+
+    #[error("No value was given for the template variables: {}", .0.join(", "))]
+    MissingTemplateVariables(Vec<String>),
+
+    #[error("The template path `{0}` is outside of the project directory")]
+    TemplatePathTraversal(String),
+
+    #[error(transparent)]
+    Render(#[from] minijinja::Error),
+
     #[error("{0}")]
     CommandFailed(String),
 
+    #[error("The `{0}` hook `{1}` failed: {2}")]
+    HookFailed(HookStage, String, Box<Error>),
+
+    #[error("The command `{0}` can't be parsed: {1}")]
+    InvalidCommand(String, String),
+
     #[error(transparent)]
     Indicatif(#[from] indicatif::style::TemplateError),
 }
@@ -1,10 +1,21 @@
-use crate::{config::Config, template::Template};
+use crate::{
+    config::Config,
+    error::Error,
+    template::{BuildOptions, HookStage, Hooks, RenderedTemplate, Template},
+};
 use fs_err as fs;
-use std::{collections::HashMap, path::PathBuf};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 use turbosql::Turbosql;
 
-#[derive(Turbosql, Default, Debug, PartialEq, Eq, Clone)]
+#[derive(Turbosql, Serialize, Deserialize, Default, Debug, PartialEq, Eq, Clone)]
 pub struct Project {
+    /// The id is specific to a database, so it isn't carried over when exporting
+    #[serde(skip)]
     pub rowid: Option<i64>,
     pub name: Option<String>,
     pub desc: Option<String>,
@@ -12,6 +23,10 @@ pub struct Project {
     pub tags: Option<Vec<String>>,
     pub language: Option<String>,
     pub category: Option<String>,
+    /// Templates the project was created with
+    pub templates: Option<Vec<String>>,
+    /// Archived projects are kept in the database but hidden from `list`
+    pub archived: Option<bool>,
 }
 
 impl Project {
@@ -30,28 +45,234 @@ impl Project {
             tags: Some(tags),
             language,
             category,
+            templates: None,
+            archived: None,
         }
     }
 
-    pub fn build(&mut self, dir: Option<PathBuf>, config: &Config, templates: Vec<String>) -> crate::utils::Result<()> {
-        let dir = if let Some(dir) = dir {
-            self.directory = Some(dir.clone());
-            dir
-        } else {
-            let dir = config.gen_project_folder(self)?;
-            self.directory = Some(dir.clone());
-            dir
+    /// Build the project in its directory, which has to be missing or empty
+    ///
+    /// Commands and hooks run in the directory the project keeps, so any paths they record stay valid. If any step
+    /// fails, everything the build created is removed again. The returned directory can undo the build later on.
+    pub fn build(
+        &mut self,
+        dir: Option<PathBuf>,
+        config: &Config,
+        templates: Vec<String>,
+        options: &BuildOptions,
+    ) -> crate::utils::Result<BuiltDirectory> {
+        let dir = self.set_directory(dir, config)?;
+        self.templates = Some(templates.clone());
+
+        let template_files = load_template_files(config, &templates)?;
+        let hooks = Hooks::collect(config, &templates, &template_files);
+
+        let cwd = std::env::current_dir()?;
+        hooks
+            .run(HookStage::PreRender, &cwd, self, options)
+            .map_err(|e| Error::BuildFailed(BuildStep::Hook(HookStage::PreRender), Box::new(e)))?;
+
+        let rendered = Template::render_templates(&dir, &templates, &template_files, config, self, options)?;
+
+        if dir.read_dir().is_ok_and(|mut entries| entries.next().is_some()) {
+            return Err(Error::DirectoryNotEmpty(dir));
+        }
+
+        let built = BuiltDirectory {
+            existed: dir.exists(),
+            path: dir,
+        };
+        match self.build_files(&built.path, &rendered, &hooks, options) {
+            Ok(()) => Ok(built),
+            Err((step, e)) => {
+                built.remove();
+                Err(Error::BuildFailed(step, Box::new(e)))
+            },
+        }
+    }
+
+    fn build_files(
+        &self,
+        dir: &Path,
+        rendered: &[RenderedTemplate],
+        hooks: &Hooks,
+        options: &BuildOptions,
+    ) -> Result<(), (BuildStep, Error)> {
+        fs::create_dir_all(dir).map_err(|e| (BuildStep::CreateDirectory, e.into()))?;
+
+        for template in rendered {
+            template
+                .write_files(dir, options.policy)
+                .map_err(|e| (BuildStep::WriteFiles, e))?;
+            for command in &template.commands {
+                command
+                    .run(dir, &template.env, &options.output)
+                    .map_err(|e| (BuildStep::Command(command.to_string()), e))?;
+            }
+        }
+
+        hooks
+            .run(HookStage::PostRender, dir, self, options)
+            .map_err(|e| (BuildStep::Hook(HookStage::PostRender), e))?;
+
+        Ok(())
+    }
+
+    /// The hooks from the config and from the templates the project was built with
+    pub fn hooks(&self, config: &Config) -> crate::utils::Result<Hooks> {
+        let templates = self.templates.clone().unwrap_or_default();
+        let template_files = load_template_files(config, &templates)?;
+        Ok(Hooks::collect(config, &templates, &template_files))
+    }
+
+    /// Render the templates the project would be built with, without touching the disk
+    pub fn render(
+        &mut self,
+        dir: Option<PathBuf>,
+        config: &Config,
+        templates: Vec<String>,
+        options: &BuildOptions,
+    ) -> crate::utils::Result<Vec<RenderedTemplate>> {
+        let dir = self.set_directory(dir, config)?;
+        self.templates = Some(templates.clone());
+
+        let template_files = load_template_files(config, &templates)?;
+
+        Template::render_templates(&dir, &templates, &template_files, config, self, options)
+    }
+
+    /// Use the given directory for the project, generating one if nothing is given
+    fn set_directory(&mut self, dir: Option<PathBuf>, config: &Config) -> crate::utils::Result<PathBuf> {
+        let dir = match dir {
+            Some(dir) => dir,
+            None => config.gen_project_folder(self)?,
         };
+        // Commands run inside the directory, so a relative path in `PO_PROJECT_DIR` would point somewhere else
+        let dir = std::path::absolute(&dir).unwrap_or(dir);
+        self.directory = Some(dir.clone());
+        Ok(dir)
+    }
+}
+
+/// The directory a project was built in, and whether it was there before the build
+#[derive(Debug)]
+pub struct BuiltDirectory {
+    pub path: PathBuf,
+    pub existed: bool,
+}
+
+impl BuiltDirectory {
+    /// Undo the build, leaving a directory that was already there empty rather than removing it
+    pub fn remove(&self) {
+        if !self.existed {
+            let _ = fs::remove_dir_all(&self.path);
+            return;
+        }
+
+        for entry in fs::read_dir(&self.path).into_iter().flatten().flatten() {
+            let path = entry.path();
+            let _ = match path.is_dir() && !path.is_symlink() {
+                true => fs::remove_dir_all(path),
+                false => fs::remove_file(path),
+            };
+        }
+    }
+}
+
+/// The steps of building a project that change the disk or the database
+#[derive(Debug)]
+pub enum BuildStep {
+    CreateDirectory,
+    WriteFiles,
+    Command(String),
+    Hook(HookStage),
+    Register,
+}
+
+impl Display for BuildStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildStep::CreateDirectory => write!(f, "creating the project directory"),
+            BuildStep::WriteFiles => write!(f, "writing the template files"),
+            BuildStep::Command(command) => write!(f, "running `{command}`"),
+            BuildStep::Hook(stage) => write!(f, "running the `{stage}` hooks"),
+            BuildStep::Register => write!(f, "adding the project to the database"),
+        }
+    }
+}
+
+fn load_template_files(config: &Config, templates: &[String]) -> crate::utils::Result<HashMap<String, Template>> {
+    if templates.is_empty() {
+        Ok(HashMap::new())
+    } else {
+        Template::load_resolved(config, templates)
+    }
+}
 
-        let template_files = if templates.is_empty() {
-            HashMap::new()
-        } else {
-            Template::load_templates(config)?
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_build() -> Result<()> {
+        let test_dir = Path::new("tests/test_files/project_build");
+        if test_dir.exists() {
+            fs::remove_dir_all(test_dir)?;
+        }
+        fs::create_dir_all(test_dir)?;
+        let config = Config {
+            template_dir: Some(PathBuf::from("tests/templates")),
+            ..Default::default()
         };
+        let mut project = Project::new(Some("Test".to_owned()), None, vec![], None, None);
+
+        let dir = test_dir.join("failing");
+        let result = project.build(
+            Some(dir.clone()),
+            &config,
+            vec!["failing".to_owned()],
+            &BuildOptions::default(),
+        );
+        match result {
+            Err(Error::BuildFailed(BuildStep::Command(command), _)) => assert_eq!(command, "false"),
+            result => panic!("expected the command to fail, got {result:?}"),
+        }
+        // Nothing is left behind, and a directory that was already there is only emptied
+        assert_eq!(fs::read_dir(test_dir)?.count(), 0);
+        fs::create_dir(&dir)?;
+        let result = project.build(
+            Some(dir.clone()),
+            &config,
+            vec!["failing".to_owned()],
+            &BuildOptions::default(),
+        );
+        assert!(result.is_err());
+        assert_eq!(fs::read_dir(&dir)?.count(), 0);
+        fs::remove_dir(&dir)?;
 
-        fs::create_dir_all(&dir)?;
+        // Commands and hooks run in the directory the project ends up in
+        let config = Config {
+            hooks: Some(serde_yaml::from_str(
+                r#"
+post_render:
+  - cmd: test "$PO_PROJECT_DIR" -ef . && test -f "$PO_PROJECT_DIR/file_3.txt"
+    shell: true
+"#,
+            )?),
+            ..config
+        };
+        let dir = test_dir.join("built");
+        let options = BuildOptions {
+            variables: HashMap::from([("number".to_owned(), "1".to_owned()), ("number2".to_owned(), "2".to_owned())]),
+            ..Default::default()
+        };
+        project.build(Some(dir.clone()), &config, vec!["test".to_owned()], &options)?;
+        assert!(dir.join("template_dir/1/file_2.txt").is_file());
+        assert_eq!(fs::read_dir(test_dir)?.count(), 1);
 
-        Template::build_templates(dir, templates, &template_files, config)?;
+        let result = project.build(Some(dir), &config, vec![], &options);
+        assert!(matches!(result, Err(Error::DirectoryNotEmpty(_))));
 
         Ok(())
     }
@@ -0,0 +1,490 @@
+use super::output::{CommandOutput, Stream};
+use crate::{error::Error, utils::Result};
+use minijinja::{Environment, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How long to keep reading output after a command exits, a process it left running can hold the pipes open forever
+const OUTPUT_DRAIN: Duration = Duration::from_millis(500);
+
+/// A command run in the project directory after the files of a template are written
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+pub enum TemplateCommand {
+    /// A command line, split into words with shell quoting rules but not run by a shell
+    Line(String),
+    Spec(CommandSpec),
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct CommandSpec {
+    /// The program to run as it is written, or the whole script when `shell` is set
+    pub cmd: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// The directory to run in, relative to the project directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Seconds to wait before the command is killed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Run `cmd` with `sh -c` so pipes, `&&`, and redirects work
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shell: bool,
+    /// An expression over the template variables, the command only runs when it is true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+    /// Keep building the project when the command fails
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_failure: bool,
+    /// How many more times to run the command when it fails
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub retries: u32,
+    /// Skip the command when this path, relative to the project directory, already exists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub if_missing: Option<PathBuf>,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+impl TemplateCommand {
+    /// The full description of the command, parsing a command line into its parts
+    pub fn spec(&self) -> Result<CommandSpec> {
+        match self {
+            TemplateCommand::Line(line) => parse_line(line),
+            // Paths can have spaces and backslashes, so `cmd` isn't split and the arguments only come from `args`
+            TemplateCommand::Spec(spec) if spec.cmd.trim().is_empty() => Err(Error::InvalidCommand(
+                spec.cmd.clone(),
+                "there is no command to run".to_owned(),
+            )),
+            TemplateCommand::Spec(spec) => Ok(spec.clone()),
+        }
+    }
+
+    /// Whether the `when` condition of the command holds for the template variables
+    pub fn should_run(&self, variables: &HashMap<String, String>) -> Result<bool> {
+        let when = match self {
+            TemplateCommand::Spec(CommandSpec { when: Some(when), .. }) => when,
+            _ => return Ok(true),
+        };
+
+        // Boolean variables are stored as text, but `when: use_ci` should be false for "false"
+        let context = variables
+            .iter()
+            .map(|(key, value)| {
+                let value = match value.as_str() {
+                    "true" => Value::from(true),
+                    "false" => Value::from(false),
+                    value => Value::from(value),
+                };
+                (key.clone(), value)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let env = Environment::new();
+        Ok(env.compile_expression(when)?.eval(context)?.is_true())
+    }
+
+    /// Run the command in `dir`, its own `env` is set on top of the given environment
+    pub fn run(&self, dir: &Path, env: &BTreeMap<String, String>, output: &CommandOutput) -> Result<()> {
+        let spec = self.spec()?;
+        let display = self.to_string();
+
+        if let Some(path) = &spec.if_missing {
+            if dir.join(path).exists() {
+                output.skip(&display, &format!("`{}` already exists", path.display()));
+                return Ok(());
+            }
+        }
+
+        let mut attempt = 0;
+        loop {
+            match spec.run(dir, env, &display, output) {
+                Err(Error::CommandFailed(_)) if attempt < spec.retries => {
+                    attempt += 1;
+                    output.notice(&format!("Retrying `{display}` ({attempt} of {})", spec.retries));
+                },
+                Err(Error::CommandFailed(message)) if spec.allow_failure => {
+                    output.notice(&format!("Continuing, `{display}` is allowed to fail: {message}"));
+                    return Ok(());
+                },
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Display for TemplateCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TemplateCommand::Line(line) => write!(f, "{line}"),
+            TemplateCommand::Spec(spec) if spec.shell && spec.args.is_empty() => write!(f, "{}", spec.cmd),
+            TemplateCommand::Spec(spec) if spec.shell => write!(f, "{} {}", spec.cmd, shell_words::join(&spec.args)),
+            TemplateCommand::Spec(spec) => write!(f, "{}", shell_words::join(std::iter::once(&spec.cmd).chain(&spec.args))),
+        }
+    }
+}
+
+impl CommandSpec {
+    fn run(&self, dir: &Path, env: &BTreeMap<String, String>, display: &str, output: &CommandOutput) -> Result<()> {
+        let mut cmd = if self.shell && cfg!(windows) {
+            let mut cmd = Command::new("cmd");
+            cmd.arg("/C").arg(&self.cmd).args(&self.args);
+            cmd
+        } else if self.shell {
+            let mut cmd = Command::new("sh");
+            // The first argument after the script becomes `$0`, so the script sees `args` as `$1` onwards
+            cmd.arg("-c").arg(&self.cmd).arg("sh").args(&self.args);
+            cmd
+        } else {
+            let mut cmd = Command::new(&self.cmd);
+            cmd.args(&self.args);
+            cmd
+        };
+
+        let cwd = match &self.cwd {
+            Some(cwd)
+                if !cwd
+                    .components()
+                    .all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) =>
+            {
+                return Err(Error::TemplatePathTraversal(cwd.display().to_string()))
+            },
+            Some(cwd) => dir.join(cwd),
+            None => dir.to_path_buf(),
+        };
+        let own_env = self.env.iter().collect::<Vec<_>>();
+        let mut run = output.begin(display, &cwd, &own_env);
+
+        cmd.current_dir(cwd)
+            .envs(env)
+            .envs(&self.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                run.finish(None);
+                return Err(Error::CommandFailed(format!("Command '{display}' failed with error: {e}")));
+            },
+        };
+
+        // Read both streams as they are written, a full pipe would block the command forever
+        let (sender, receiver) = mpsc::channel();
+        let readers = [
+            read_lines(child.stdout.take().unwrap(), Stream::Stdout, sender.clone()),
+            read_lines(child.stderr.take().unwrap(), Stream::Stderr, sender),
+        ];
+
+        let timeout = self.timeout.map(Duration::from_secs);
+        let start = Instant::now();
+        let mut stderr = Vec::new();
+        let mut status = None;
+        let mut exited: Option<Instant> = None;
+        let mut closed = false;
+        loop {
+            match receiver.recv_timeout(Duration::from_millis(20)) {
+                Ok((stream, line)) => {
+                    run.line(stream, &line);
+                    if stream == Stream::Stderr {
+                        stderr.push(line);
+                    }
+                },
+                // Both streams are closed and the command has exited
+                Err(RecvTimeoutError::Disconnected) if status.is_some() => {
+                    closed = true;
+                    break;
+                },
+                Err(_) => {},
+            }
+
+            if exited.is_some_and(|exited| exited.elapsed() >= OUTPUT_DRAIN) {
+                break;
+            }
+            if status.is_none() {
+                status = child.try_wait()?;
+                if status.is_some() {
+                    exited = Some(Instant::now());
+                }
+            }
+            if status.is_none() && timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+                let _ = child.kill();
+                let _ = child.wait();
+                run.finish(None);
+                return Err(Error::CommandFailed(format!(
+                    "Command '{display}' timed out after {} seconds",
+                    self.timeout.unwrap_or_default()
+                )));
+            }
+        }
+        // Readers still blocked on a pipe held open by another process are left to finish on their own
+        if closed {
+            for reader in readers {
+                let _ = reader.join();
+            }
+        }
+
+        run.finish(status);
+        match status {
+            Some(status) if !status.success() && stderr.is_empty() => {
+                Err(Error::CommandFailed(format!("Command '{display}' failed with {status}")))
+            },
+            Some(status) if !status.success() => Err(Error::CommandFailed(format!(
+                "Command '{display}' failed with error: {}",
+                stderr.join("\n")
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn read_lines(stream: impl Read + Send + 'static, kind: Stream, sender: Sender<(Stream, String)>) -> JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            match line {
+                Ok(line) => {
+                    if sender.send((kind, line)).is_err() {
+                        break;
+                    }
+                },
+                Err(_) => break,
+            }
+        }
+    })
+}
+
+/// Split a command line into a command, leading `KEY=VALUE` environment assignments are set for the command
+fn parse_line(line: &str) -> Result<CommandSpec> {
+    let words = match shell_words::split(line) {
+        Ok(words) => words,
+        Err(e) => return Err(Error::InvalidCommand(line.to_owned(), e.to_string())),
+    };
+
+    let mut env = BTreeMap::new();
+    let mut words = words.into_iter().peekable();
+    while let Some((key, value)) = words.peek().and_then(|word| env_assignment(word)) {
+        env.insert(key, value);
+        words.next();
+    }
+
+    match words.next() {
+        Some(cmd) => Ok(CommandSpec {
+            cmd,
+            args: words.collect(),
+            env,
+            ..Default::default()
+        }),
+        None => Err(Error::InvalidCommand(
+            line.to_owned(),
+            "there is no command to run".to_owned(),
+        )),
+    }
+}
+
+fn env_assignment(word: &str) -> Option<(String, String)> {
+    let (key, value) = word.split_once('=')?;
+    let mut chars = key.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then(|| (key.to_owned(), value.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::Verbosity;
+    use fs_err as fs;
+
+    #[test]
+    fn test_parse_line() {
+        let spec = parse_line(r#"GIT_AUTHOR_NAME="Test User" git commit -m "initial commit""#).unwrap();
+        assert_eq!(spec.cmd, "git");
+        assert_eq!(spec.args, vec!["commit", "-m", "initial commit"]);
+        assert_eq!(spec.env["GIT_AUTHOR_NAME"], "Test User");
+
+        assert!(matches!(parse_line("echo \"unclosed"), Err(Error::InvalidCommand(..))));
+        assert!(matches!(parse_line("A=1"), Err(Error::InvalidCommand(..))));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let commands: Vec<TemplateCommand> = serde_yaml::from_str(
+            "
+- git init
+- cmd: echo hi > out.txt
+  shell: true
+- cmd: cargo
+  args: [init, --bin]
+  cwd: app
+  env:
+    CARGO_NAME: test
+  timeout: 60
+",
+        )
+        .unwrap();
+
+        assert_eq!(commands[0], TemplateCommand::Line("git init".to_owned()));
+        assert!(commands[1].spec().unwrap().shell);
+        let spec = commands[2].spec().unwrap();
+        assert_eq!(spec.cwd, Some(PathBuf::from("app")));
+        assert_eq!(spec.timeout, Some(60));
+        assert_eq!(commands[2].to_string(), "cargo init --bin");
+
+        // The program of the structured form is never split, however it's written
+        let command = TemplateCommand::Spec(CommandSpec {
+            cmd: r"C:\Program Files\Tool\tool.exe".to_owned(),
+            args: vec!["--name".to_owned(), "my app".to_owned()],
+            ..Default::default()
+        });
+        let spec = command.spec().unwrap();
+        assert_eq!(spec.cmd, r"C:\Program Files\Tool\tool.exe");
+        assert_eq!(spec.args, vec!["--name", "my app"]);
+        assert!(spec.env.is_empty());
+        assert_eq!(command.to_string(), r"'C:\Program Files\Tool\tool.exe' --name 'my app'");
+    }
+
+    #[test]
+    fn test_should_run() {
+        let commands: Vec<TemplateCommand> = serde_yaml::from_str(
+            r#"
+- cargo init
+- cmd: cargo
+  args: [init, --bin]
+  when: kind == "bin"
+- cmd: cargo
+  args: [init, --lib]
+  when: kind == "lib" and not ci
+"#,
+        )
+        .unwrap();
+        let variables = HashMap::from([("kind".to_owned(), "bin".to_owned()), ("ci".to_owned(), "false".to_owned())]);
+
+        let runs = commands
+            .iter()
+            .map(|command| command.should_run(&variables).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(runs, vec![true, true, false]);
+
+        let variables = HashMap::from([("kind".to_owned(), "lib".to_owned())]);
+        assert!(commands[2].should_run(&variables).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_options() {
+        let test_dir = Path::new("tests/test_files/template_command_options");
+        if test_dir.exists() {
+            fs::remove_dir_all(test_dir).unwrap();
+        }
+        fs::create_dir_all(test_dir).unwrap();
+        let output = CommandOutput {
+            verbosity: Verbosity::Quiet,
+            ..Default::default()
+        };
+
+        // Fails the first two times, then succeeds
+        let command = TemplateCommand::Spec(CommandSpec {
+            cmd: "echo x >> attempts; [ $(wc -l < attempts) -ge 3 ]".to_owned(),
+            shell: true,
+            retries: 2,
+            ..Default::default()
+        });
+        command.run(test_dir, &BTreeMap::new(), &output).unwrap();
+        assert_eq!(fs::read_to_string(test_dir.join("attempts")).unwrap().lines().count(), 3);
+
+        let command = TemplateCommand::Spec(CommandSpec {
+            cmd: "false".to_owned(),
+            allow_failure: true,
+            ..Default::default()
+        });
+        command.run(test_dir, &BTreeMap::new(), &output).unwrap();
+
+        let command = TemplateCommand::Spec(CommandSpec {
+            cmd: "false".to_owned(),
+            if_missing: Some(PathBuf::from("attempts")),
+            ..Default::default()
+        });
+        command.run(test_dir, &BTreeMap::new(), &output).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run() {
+        let test_dir = Path::new("tests/test_files/template_command");
+        if test_dir.exists() {
+            fs::remove_dir_all(test_dir).unwrap();
+        }
+        fs::create_dir_all(test_dir.join("sub")).unwrap();
+
+        let command = TemplateCommand::Spec(CommandSpec {
+            cmd: "echo \"$GREETING $1\" | tr a-z A-Z > out.txt".to_owned(),
+            args: vec!["world".to_owned()],
+            cwd: Some(PathBuf::from("sub")),
+            env: BTreeMap::from([("GREETING".to_owned(), "hello".to_owned())]),
+            shell: true,
+            ..Default::default()
+        });
+        command.run(test_dir, &BTreeMap::new(), &CommandOutput::default()).unwrap();
+        assert_eq!(fs::read_to_string(test_dir.join("sub/out.txt")).unwrap(), "HELLO WORLD\n");
+
+        let output = CommandOutput {
+            verbosity: Verbosity::Quiet,
+            log_file: Some(test_dir.join("commands.log")),
+            ..Default::default()
+        };
+        let command = TemplateCommand::Line("sh -c 'echo to stdout; echo to stderr >&2; exit 3'".to_owned());
+        match command.run(test_dir, &BTreeMap::new(), &output) {
+            Err(Error::CommandFailed(message)) => assert!(message.ends_with("to stderr")),
+            result => panic!("expected the command to fail, got {result:?}"),
+        }
+        let log = fs::read_to_string(test_dir.join("commands.log")).unwrap();
+        assert!(log.contains("out | to stdout\nerr | to stderr\n") || log.contains("err | to stderr\nout | to stdout\n"));
+        assert!(log.contains("exit status: 3"));
+
+        let command = TemplateCommand::Spec(CommandSpec {
+            cmd: "sleep".to_owned(),
+            args: vec!["5".to_owned()],
+            timeout: Some(0),
+            ..Default::default()
+        });
+        assert!(matches!(
+            command.run(test_dir, &BTreeMap::new(), &CommandOutput::default()),
+            Err(Error::CommandFailed(_))
+        ));
+
+        // A process left running in the background keeps the output open, but the command has still finished
+        let command = TemplateCommand::Spec(CommandSpec {
+            cmd: "sleep 30 & echo started".to_owned(),
+            shell: true,
+            ..Default::default()
+        });
+        let start = Instant::now();
+        command.run(test_dir, &BTreeMap::new(), &output).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let command = TemplateCommand::Line("ls ../..".to_owned());
+        assert!(command.run(test_dir, &BTreeMap::new(), &CommandOutput::default()).is_ok());
+        let command = TemplateCommand::Spec(CommandSpec {
+            cmd: "ls".to_owned(),
+            cwd: Some(PathBuf::from("../")),
+            ..Default::default()
+        });
+        assert!(matches!(
+            command.run(test_dir, &BTreeMap::new(), &CommandOutput::default()),
+            Err(Error::TemplatePathTraversal(_))
+        ));
+    }
+}
@@ -0,0 +1,227 @@
+use super::{referenced_variables, template_root, Engine, Template, TemplateVar, BUILTIN_VARIABLES};
+use crate::{config::Config, error::Error, utils::Result};
+use fs_err as fs;
+use ignore::WalkBuilder;
+use std::path::{Component, Path, PathBuf};
+
+/// The files of the source directory that couldn't be copied into the template as they are
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CreateReport {
+    /// Files that aren't text, so they were left out
+    pub skipped: Vec<PathBuf>,
+    /// Files whose contents or path already have `${name}` placeholders, which are filled in when the template is
+    /// rendered just like its own variables
+    pub placeholders: Vec<(PathBuf, Vec<String>)>,
+}
+
+/// Turn an existing directory into a template, returning the template and what couldn't be copied as it is
+///
+/// Each `(literal, variable)` pair in `replacements` swaps the literal for a `${variable}` placeholder in both the
+/// contents and the paths of the copied files.
+pub fn create_template(
+    name: &str,
+    desc: Option<String>,
+    source: &Path,
+    replacements: &[(String, String)],
+    config: &Config,
+) -> Result<(Template, CreateReport)> {
+    let mut components = Path::new(name).components();
+    if !matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) {
+        return Err(Error::InvalidTemplateName(name.to_owned()));
+    }
+
+    let root = template_root(config)?;
+    let files_dir = root.join(name);
+    let template_file = root.join(format!("{name}.yaml"));
+    if files_dir.exists() || template_file.exists() || Template::load_templates(config)?.contains_key(name) {
+        return Err(Error::TemplateExists(name.to_owned()));
+    }
+
+    let mut report = CreateReport::default();
+    let walker = WalkBuilder::new(source)
+        .hidden(false)
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+
+    for entry in walker {
+        let entry = entry?;
+        if !entry.file_type().is_some_and(|file_type| file_type.is_file()) {
+            continue;
+        }
+
+        let relative = entry.path().strip_prefix(source).unwrap_or(entry.path());
+        // Templates are rendered as text, so files that aren't can't be part of one
+        let contents = match String::from_utf8(fs::read(entry.path())?) {
+            Ok(contents) => contents,
+            Err(_) => {
+                report.skipped.push(relative.to_path_buf());
+                continue;
+            },
+        };
+
+        // The legacy engine has no way to escape a placeholder, so the ones already there can only be pointed out
+        let path = relative.to_string_lossy();
+        let mut placeholders = referenced_variables(&contents, Engine::Legacy)?;
+        placeholders.extend(referenced_variables(&path, Engine::Legacy)?);
+        if !placeholders.is_empty() {
+            let mut placeholders = placeholders.into_iter().collect::<Vec<_>>();
+            placeholders.sort();
+            report.placeholders.push((relative.to_path_buf(), placeholders));
+        }
+
+        let file = files_dir.join(replace_literals(&path, replacements));
+        fs::create_dir_all(file.parent().unwrap())?;
+        fs::write(file, replace_literals(&contents, replacements))?;
+    }
+
+    let mut template_vars: Vec<TemplateVar> = Vec::new();
+    for (_, var) in replacements {
+        let declared = template_vars.iter().any(|declared| declared.spec().name == *var);
+        if !declared && !BUILTIN_VARIABLES.contains(&var.as_str()) {
+            template_vars.push(TemplateVar::Name(var.clone()));
+        }
+    }
+
+    let template = Template {
+        name: name.to_owned(),
+        desc,
+        template_dir: Some(PathBuf::from(name)),
+        contents_only: true,
+        template_vars: (!template_vars.is_empty()).then_some(template_vars),
+        ..Default::default()
+    };
+    fs::create_dir_all(&files_dir)?;
+    fs::write(template_file, serde_yaml::to_string(&template)?)?;
+
+    Ok((template, report))
+}
+
+/// Swap each literal for its placeholder in a single pass, taking the longest literal that matches at each position
+///
+/// Text that was already replaced is never searched again, so a literal can't match inside an inserted placeholder.
+fn replace_literals(contents: &str, replacements: &[(String, String)]) -> String {
+    let mut output = String::with_capacity(contents.len());
+    let mut rest = contents;
+
+    while let Some(c) = rest.chars().next() {
+        let longest = replacements
+            .iter()
+            .filter(|(literal, _)| !literal.is_empty() && rest.starts_with(literal.as_str()))
+            .max_by_key(|(literal, _)| literal.len());
+
+        match longest {
+            Some((literal, var)) => {
+                output.push_str(&format!("${{{var}}}"));
+                rest = &rest[literal.len()..];
+            },
+            None => {
+                output.push(c);
+                rest = &rest[c.len_utf8()..];
+            },
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_replace_literals() {
+        let replacements = vec![
+            ("MyApp".to_owned(), "project_name".to_owned()),
+            ("name".to_owned(), "foo".to_owned()),
+            ("MyAppCore".to_owned(), "core".to_owned()),
+        ];
+
+        // `name` is part of the placeholder inserted for `MyApp`, but that text is never searched again
+        assert_eq!(
+            replace_literals("MyApp has a name, MyAppCore", &replacements),
+            "${project_name} has a ${foo}, ${core}"
+        );
+    }
+
+    #[test]
+    fn test_create_template() -> Result<()> {
+        let test_dir = Path::new("tests/test_files/create_template");
+        if test_dir.exists() {
+            fs::remove_dir_all(test_dir)?;
+        }
+        let source = test_dir.join("MyApp");
+        fs::create_dir_all(source.join("src"))?;
+        fs::create_dir_all(source.join("target"))?;
+        fs::create_dir_all(source.join(".git"))?;
+        fs::write(source.join(".gitignore"), "target/\n")?;
+        fs::write(source.join("target/out.txt"), "build output")?;
+        fs::write(source.join(".git/HEAD"), "ref: refs/heads/main")?;
+        fs::write(source.join("src/MyApp.txt"), "MyApp by Jane, MyAppCore")?;
+        fs::write(source.join("logo.bin"), [0xff, 0xfe, 0x00])?;
+        fs::write(source.join("run.sh"), "echo ${HOME} ${year}")?;
+
+        let config = Config {
+            template_dir: Some(test_dir.join("templates")),
+            ..Default::default()
+        };
+        fs::create_dir_all(test_dir.join("templates"))?;
+
+        let replacements = vec![
+            ("MyApp".to_owned(), "project_name".to_owned()),
+            ("MyAppCore".to_owned(), "core".to_owned()),
+            ("Jane".to_owned(), "author".to_owned()),
+        ];
+        let (template, report) = create_template("app", None, &source, &replacements, &config)?;
+
+        assert_eq!(report.skipped, vec![PathBuf::from("logo.bin")]);
+        assert_eq!(
+            report.placeholders,
+            vec![(PathBuf::from("run.sh"), vec!["HOME".to_owned(), "year".to_owned()])]
+        );
+        assert_eq!(
+            template.template_vars,
+            Some(vec![
+                TemplateVar::Name("core".to_owned()),
+                TemplateVar::Name("author".to_owned())
+            ])
+        );
+
+        let files_dir = test_dir.join("templates/app");
+        assert_eq!(
+            fs::read_to_string(files_dir.join("src/${project_name}.txt"))?,
+            "${project_name} by ${author}, ${core}"
+        );
+        assert!(files_dir.join(".gitignore").exists());
+        assert!(!files_dir.join("target").exists());
+        assert!(!files_dir.join(".git").exists());
+
+        let templates = Template::load_templates(&config)?;
+        assert_eq!(templates["app"], template);
+        assert_eq!(
+            template.validate(&config)?,
+            vec!["the variable `HOME` is used but never declared"]
+        );
+
+        let variables = HashMap::from([
+            ("project_name".to_owned(), "Demo".to_owned()),
+            ("core".to_owned(), "Engine".to_owned()),
+            ("author".to_owned(), "Sam".to_owned()),
+        ]);
+        let files = crate::template::load_files(&files_dir, &files_dir, &variables, template.engine)?;
+        assert_eq!(files[Path::new("src/Demo.txt")], "Demo by Sam, Engine");
+
+        assert!(matches!(
+            create_template("app", None, &source, &[], &config),
+            Err(Error::TemplateExists(_))
+        ));
+        assert!(matches!(
+            create_template("../app", None, &source, &[], &config),
+            Err(Error::InvalidTemplateName(_))
+        ));
+
+        Ok(())
+    }
+}
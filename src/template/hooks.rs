@@ -0,0 +1,185 @@
+use super::{builtin_variables, project_env, BuildOptions, Template, TemplateCommand};
+use crate::{config::Config, error::Error, project::Project, utils::Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+
+/// Commands run at fixed points while a project is built, set globally in the config or by a template
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct Hooks {
+    /// Before any template is rendered or any file is written
+    ///
+    /// The project directory may not exist yet, so these run in the directory the command was started from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_render: Vec<TemplateCommand>,
+    /// After every template has been written into the project and its commands have run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_render: Vec<TemplateCommand>,
+    /// After the project has been added to the database
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_register: Vec<TemplateCommand>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    PreRender,
+    PostRender,
+    PostRegister,
+}
+
+impl Display for HookStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookStage::PreRender => write!(f, "pre_render"),
+            HookStage::PostRender => write!(f, "post_render"),
+            HookStage::PostRegister => write!(f, "post_register"),
+        }
+    }
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_render.is_empty() && self.post_render.is_empty() && self.post_register.is_empty()
+    }
+
+    /// The hooks from the config followed by the hooks of each template, in the order the templates are built
+    pub fn collect(config: &Config, templates: &[String], template_files: &HashMap<String, Template>) -> Hooks {
+        let mut hooks = config.hooks.clone().unwrap_or_default();
+
+        for template in templates.iter().filter_map(|name| template_files.get(name)) {
+            hooks.extend(&template.hooks);
+        }
+
+        hooks
+    }
+
+    /// Add the hooks of each stage after the ones already there
+    pub fn extend(&mut self, other: &Hooks) {
+        self.pre_render.extend(other.pre_render.iter().cloned());
+        self.post_render.extend(other.post_render.iter().cloned());
+        self.post_register.extend(other.post_register.iter().cloned());
+    }
+
+    fn commands(&self, stage: HookStage) -> &[TemplateCommand] {
+        match stage {
+            HookStage::PreRender => &self.pre_render,
+            HookStage::PostRender => &self.post_render,
+            HookStage::PostRegister => &self.post_register,
+        }
+    }
+
+    /// The commands of every stage, in the order the stages run
+    pub fn stages(&self) -> impl Iterator<Item = (HookStage, &TemplateCommand)> {
+        [HookStage::PreRender, HookStage::PostRender, HookStage::PostRegister]
+            .into_iter()
+            .flat_map(move |stage| self.commands(stage).iter().map(move |command| (stage, command)))
+    }
+
+    /// Run the hooks of a stage in `cwd`, with the fields of the project in `PO_PROJECT_*` environment variables
+    pub fn run(&self, stage: HookStage, cwd: &Path, project: &Project, options: &BuildOptions) -> Result<()> {
+        let commands = self.commands(stage);
+        if commands.is_empty() {
+            return Ok(());
+        }
+
+        let dir = project.directory.clone().unwrap_or_default();
+        let mut variables = builtin_variables(project, &dir);
+        variables.extend(options.variables.clone());
+        let env = project_env(project, &dir);
+
+        for command in commands {
+            if command.should_run(&variables)? {
+                command
+                    .run(cwd, &env, &options.output)
+                    .map_err(|e| Error::HookFailed(stage, command.to_string(), Box::new(e)))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::{CommandOutput, Verbosity};
+    use fs_err as fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_collect() {
+        let config = Config {
+            hooks: Some(serde_yaml::from_str("post_register: [global]").unwrap()),
+            ..Default::default()
+        };
+        let template: Template = serde_yaml::from_str(
+            "
+name: hooked
+hooks:
+  pre_render: [check]
+  post_register: [local]
+",
+        )
+        .unwrap();
+        let template_files = HashMap::from([("hooked".to_owned(), template)]);
+
+        let hooks = Hooks::collect(&config, &["hooked".to_owned()], &template_files);
+        assert_eq!(hooks.pre_render, vec![TemplateCommand::Line("check".to_owned())]);
+        assert_eq!(
+            hooks.post_register,
+            vec![
+                TemplateCommand::Line("global".to_owned()),
+                TemplateCommand::Line("local".to_owned())
+            ]
+        );
+        assert_eq!(
+            hooks.stages().map(|(stage, _)| stage).collect::<Vec<_>>(),
+            vec![HookStage::PreRender, HookStage::PostRegister, HookStage::PostRegister]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run() {
+        let test_dir = Path::new("tests/test_files/hooks");
+        if test_dir.exists() {
+            fs::remove_dir_all(test_dir).unwrap();
+        }
+        fs::create_dir_all(test_dir).unwrap();
+
+        let hooks: Hooks = serde_yaml::from_str(
+            r#"
+post_register:
+  - cmd: echo "$PO_PROJECT_ID $PO_PROJECT_NAME $PO_PROJECT_TAGS" > env.txt
+    shell: true
+  - cmd: "false"
+    when: project_language == "python"
+"#,
+        )
+        .unwrap();
+        let mut project = Project::new(
+            Some("hooked".to_owned()),
+            None,
+            vec!["a".to_owned(), "b".to_owned()],
+            Some("rust".to_owned()),
+            None,
+        );
+        project.rowid = Some(7);
+        project.directory = Some(PathBuf::from("/dev/hooked"));
+        let options = BuildOptions {
+            output: CommandOutput {
+                verbosity: Verbosity::Quiet,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        hooks.run(HookStage::PostRegister, test_dir, &project, &options).unwrap();
+        assert_eq!(fs::read_to_string(test_dir.join("env.txt")).unwrap(), "7 hooked a,b\n");
+
+        project.language = Some("python".to_owned());
+        let result = hooks.run(HookStage::PostRegister, test_dir, &project, &options);
+        assert!(matches!(result, Err(Error::HookFailed(HookStage::PostRegister, ..))));
+    }
+}
@@ -0,0 +1,1319 @@
+use crate::{config::Config, error::Error, project::Project, utils::Result};
+use chrono::Local;
+use console::style;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::Confirm;
+use fs_err as fs;
+use heck::{ToKebabCase, ToLowerCamelCase, ToShoutySnakeCase, ToSnakeCase, ToTitleCase, ToUpperCamelCase};
+use minijinja::Environment;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+pub use command::{CommandSpec, TemplateCommand};
+pub use create::create_template;
+pub use hooks::{HookStage, Hooks};
+pub use output::{CommandOutput, Verbosity};
+pub use vars::{load_vars_file, TemplateVar};
+
+mod command;
+mod create;
+mod hooks;
+mod output;
+mod vars;
+
+/// How the files of a template are rendered
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Engine {
+    /// Literal `${var}` replacement, used by templates that don't choose an engine
+    #[default]
+    Legacy,
+    /// Jinja syntax with `if`/`else`, `for` loops, and filters such as `snake_case`
+    Jinja,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Template {
+    pub name: String,
+    /// The template this one builds on, its files, variables, commands, and hooks come first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Templates added to this one after `extends`, in order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub includes: Option<Vec<String>>,
+    /// What to do when two of the templates this one is made from write the same file
+    #[serde(default, skip_serializing_if = "ConflictPolicy::is_default")]
+    pub on_conflict: ConflictPolicy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desc: Option<String>,
+    #[serde(default)]
+    pub engine: Engine,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_dir: Option<PathBuf>,
+    /// Write only what is inside `template_dir` into the project, rather than the directory itself
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub contents_only: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_file: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_vars: Option<Vec<TemplateVar>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commands: Option<Vec<TemplateCommand>>,
+    #[serde(default, skip_serializing_if = "Hooks::is_empty")]
+    pub hooks: Hooks,
+    /// The templates this one extends and includes, in the order their files are written
+    #[serde(skip)]
+    pub layers: Vec<Template>,
+}
+
+/// What to do when templates combined with `extends` or `includes` write the same file
+///
+/// Files are written in order: the extended template, then each included template, then the template itself.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// The file written last is kept
+    #[default]
+    Override,
+    /// The file written first is kept
+    Keep,
+    /// Fail to build the template
+    Error,
+}
+
+impl ConflictPolicy {
+    fn is_default(&self) -> bool {
+        *self == ConflictPolicy::default()
+    }
+}
+
+/// Settings for building templates that come from the command line
+#[derive(Debug, Default, Clone)]
+pub struct BuildOptions {
+    /// Values for template variables, these take priority over the built in variables
+    pub variables: HashMap<String, String>,
+    /// Fail instead of prompting when a variable doesn't have a value
+    pub no_input: bool,
+    /// What to do with files that already exist in the project
+    pub policy: OverwritePolicy,
+    pub output: CommandOutput,
+}
+
+/// What to do when a template writes a file that already exists
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Replace the existing file
+    #[default]
+    Overwrite,
+    /// Keep the existing file
+    SkipExisting,
+    /// Ask whether to replace each existing file
+    Prompt,
+}
+
+/// The files that building templates created, changed, or left alone, relative to the project directory
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BuildSummary {
+    pub created: Vec<PathBuf>,
+    pub changed: Vec<PathBuf>,
+    pub unchanged: Vec<PathBuf>,
+    pub skipped: Vec<PathBuf>,
+}
+
+impl BuildSummary {
+    fn extend(&mut self, other: BuildSummary) {
+        self.created.extend(other.created);
+        self.changed.extend(other.changed);
+        self.unchanged.extend(other.unchanged);
+        self.skipped.extend(other.skipped);
+    }
+}
+
+impl Template {
+    /// Every template in the template directory by name, as written without what they extend or include
+    ///
+    /// When two files use the same name the first one is kept with a warning, `template validate` reports it as an
+    /// error instead.
+    pub fn load_templates(config: &Config) -> Result<HashMap<String, Template>> {
+        let mut templates: HashMap<String, (PathBuf, Template)> = HashMap::new();
+
+        for path in Template::template_paths(config)? {
+            let template = Template::load(&path)?;
+
+            if let Some((first, _)) = templates.get(&template.name) {
+                let duplicate = Error::DuplicateTemplate(template.name, first.clone(), path);
+                eprintln!("{} {duplicate}, using the first one", style("!").yellow());
+                continue;
+            }
+            templates.insert(template.name.clone(), (path, template));
+        }
+
+        Ok(templates.into_iter().map(|(name, (_, template))| (name, template)).collect())
+    }
+
+    /// The named templates combined with the templates they extend and include
+    ///
+    /// Only these are resolved, so a template elsewhere that can't be doesn't get in the way.
+    pub fn load_resolved(config: &Config, names: &[String]) -> Result<HashMap<String, Template>> {
+        let templates = Template::load_templates(config)?;
+        names
+            .iter()
+            .map(|name| Ok((name.clone(), Template::resolve(name, &templates)?)))
+            .collect()
+    }
+
+    /// Combine a template with the templates it extends and includes, following them recursively
+    pub fn resolve(name: &str, templates: &HashMap<String, Template>) -> Result<Template> {
+        let template = match templates.get(name) {
+            Some(template) => template,
+            None => return Err(Error::TemplateNotFound(name.to_owned())),
+        };
+
+        let mut layers = Vec::new();
+        collect_layers(template, templates, &mut vec![name.to_owned()], &mut layers)?;
+        Ok(template.clone().inherit(layers))
+    }
+
+    /// Merge the variables, commands, and hooks of the layers into the template
+    ///
+    /// A variable declared again replaces the earlier declaration, commands and hooks run in the order of the layers.
+    fn inherit(mut self, layers: Vec<Template>) -> Template {
+        if layers.is_empty() {
+            return self;
+        }
+
+        let mut vars: Vec<TemplateVar> = Vec::new();
+        let mut commands = Vec::new();
+        let mut hooks = Hooks::default();
+        for layer in layers.iter().chain(std::iter::once(&self)) {
+            for var in layer.template_vars.iter().flatten() {
+                let name = var.spec().name;
+                match vars.iter_mut().find(|declared| declared.spec().name == name) {
+                    Some(declared) => *declared = var.clone(),
+                    None => vars.push(var.clone()),
+                }
+            }
+            commands.extend(layer.commands.iter().flatten().cloned());
+            hooks.extend(&layer.hooks);
+        }
+
+        if self.desc.is_none() {
+            self.desc = layers
+                .iter()
+                .find(|layer| self.extends.as_ref() == Some(&layer.name))
+                .and_then(|layer| layer.desc.clone());
+        }
+        self.template_vars = (!vars.is_empty()).then_some(vars);
+        self.commands = (!commands.is_empty()).then_some(commands);
+        self.hooks = hooks;
+        self.layers = layers;
+        self
+    }
+
+    /// Every template YAML file in the template directory
+    pub fn template_paths(config: &Config) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+
+        for entry in fs::read_dir(template_root(config)?)? {
+            let path = entry?.path();
+            if path.is_file()
+                && (path.extension().unwrap_or_default() == "yaml" || path.extension().unwrap_or_default() == "yml")
+            {
+                paths.push(path);
+            }
+        }
+
+        paths.sort();
+        Ok(paths)
+    }
+
+    pub fn load(path: &Path) -> Result<Template> {
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&contents)?)
+    }
+
+    /// The files the template and its layers read from, paired with the path they are written to in the project
+    ///
+    /// A path written by more than one layer is listed once, following `on_conflict` like rendering does.
+    pub fn source_files(&self, config: &Config) -> Result<Vec<(PathBuf, PathBuf)>> {
+        // Each output path along with its source and the name of the template that wrote it
+        let mut written: BTreeMap<PathBuf, (PathBuf, String)> = BTreeMap::new();
+
+        for layer in self.layers.iter().chain(std::iter::once(self)) {
+            for (source, output) in layer.own_source_files(config)? {
+                if let Some((_, owner)) = written.get(&output) {
+                    match self.on_conflict {
+                        ConflictPolicy::Override => {},
+                        ConflictPolicy::Keep => continue,
+                        ConflictPolicy::Error => {
+                            return Err(Error::TemplateConflict(
+                                output.display().to_string(),
+                                owner.clone(),
+                                layer.name.clone(),
+                            ))
+                        },
+                    }
+                }
+                written.insert(output, (source, layer.name.clone()));
+            }
+        }
+
+        Ok(written.into_iter().map(|(output, (source, _))| (source, output)).collect())
+    }
+
+    fn own_source_files(&self, config: &Config) -> Result<Vec<(PathBuf, PathBuf)>> {
+        let root = template_root(config)?;
+        let mut files = Vec::new();
+
+        if let Some(template_dir) = &self.template_dir {
+            let template_dir = root.join(template_dir);
+            let files_root = self.files_root(&template_dir);
+            for file in list_files(&template_dir)? {
+                let relative = file.strip_prefix(files_root).unwrap_or(&file).to_path_buf();
+                files.push((file, relative));
+            }
+        }
+
+        if let Some(template_file) = &self.template_file {
+            let template_file = root.join(template_file);
+            let file_name = PathBuf::from(template_file.file_name().unwrap_or_default());
+            files.push((template_file, file_name));
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    /// The directory that the paths of the files in `template_dir` are relative to
+    fn files_root<'a>(&self, template_dir: &'a Path) -> &'a Path {
+        match self.contents_only {
+            true => template_dir,
+            // Paths are relative to the parent so the template directory itself is included
+            false => template_dir.parent().unwrap_or(template_dir),
+        }
+    }
+
+    /// Check the template for problems, returning a description of each one found
+    pub fn validate(&self, config: &Config) -> Result<Vec<String>> {
+        let root = template_root(config)?;
+        let mut problems = Vec::new();
+
+        for layer in self.layers.iter().chain(std::iter::once(self)) {
+            if let Some(template_dir) = &layer.template_dir {
+                if !root.join(template_dir).is_dir() {
+                    problems.push(format!("the template directory `{}` does not exist", template_dir.display()));
+                }
+            }
+            if let Some(template_file) = &layer.template_file {
+                if !root.join(template_file).is_file() {
+                    problems.push(format!("the template file `{}` does not exist", template_file.display()));
+                }
+            }
+        }
+        if !problems.is_empty() {
+            return Ok(problems);
+        }
+
+        let mut referenced = HashSet::new();
+        for layer in self.layers.iter().chain(std::iter::once(self)) {
+            for (source, output) in layer.own_source_files(config)? {
+                match referenced_variables(&output.to_string_lossy(), layer.engine) {
+                    Ok(vars) => referenced.extend(vars),
+                    Err(e) => problems.push(format!("the path `{}` can't be parsed: {e}", output.display())),
+                }
+                match fs::read_to_string(&source).map(|contents| referenced_variables(&contents, layer.engine)) {
+                    Ok(Ok(vars)) => referenced.extend(vars),
+                    Ok(Err(e)) => problems.push(format!("`{}` can't be parsed: {e}", output.display())),
+                    Err(_) => problems.push(format!("`{}` is not a text file", output.display())),
+                }
+            }
+        }
+
+        // A variable only used to decide whether a command runs is still used
+        let env = Environment::new();
+        for command in self.commands.iter().flatten() {
+            if let Err(e) = command.spec() {
+                problems.push(e.to_string());
+            }
+            if let TemplateCommand::Spec(CommandSpec { when: Some(when), .. }) = command {
+                match env.compile_expression(when) {
+                    Ok(expression) => referenced.extend(
+                        expression
+                            .undeclared_variables(false)
+                            .into_iter()
+                            .filter(|var| !JINJA_GLOBALS.contains(&var.as_str())),
+                    ),
+                    Err(e) => problems.push(format!("the condition `{when}` can't be parsed: {e}")),
+                }
+            }
+        }
+
+        let specs = self.template_vars.iter().flatten().map(TemplateVar::spec).collect::<Vec<_>>();
+        for spec in &specs {
+            if !referenced.contains(&spec.name) {
+                problems.push(format!("the variable `{}` is declared but never used", spec.name));
+            }
+            if let Some(default) = &spec.default {
+                if let Err(e) = spec.validate(default) {
+                    problems.push(format!("the default is invalid, {e}"));
+                }
+            }
+        }
+
+        let mut undeclared = referenced
+            .iter()
+            .filter(|var| !specs.iter().any(|spec| spec.name == **var) && !BUILTIN_VARIABLES.contains(&var.as_str()))
+            .collect::<Vec<_>>();
+        undeclared.sort();
+        for var in undeclared {
+            problems.push(format!("the variable `{var}` is used but never declared"));
+        }
+
+        Ok(problems)
+    }
+
+    pub fn build_templates(
+        path: PathBuf,
+        templates: Vec<String>,
+        template_files: &HashMap<String, Template>,
+        config: &Config,
+        project: &Project,
+        options: &BuildOptions,
+    ) -> Result<BuildSummary> {
+        let rendered = Template::render_templates(&path, &templates, template_files, config, project, options)?;
+
+        let mut summary = BuildSummary::default();
+        for template in rendered {
+            summary.extend(template.write(&path, options.policy, &options.output)?);
+        }
+
+        Ok(summary)
+    }
+
+    /// Render every template in memory without touching the project directory
+    pub fn render_templates(
+        path: &Path,
+        templates: &[String],
+        template_files: &HashMap<String, Template>,
+        config: &Config,
+        project: &Project,
+        options: &BuildOptions,
+    ) -> Result<Vec<RenderedTemplate>> {
+        let mut variables = builtin_variables(project, path);
+        variables.extend(options.variables.clone());
+
+        let templates = templates
+            .iter()
+            .map(|template_str| match template_files.get(template_str) {
+                Some(template) => Ok(template),
+                None => Err(Error::TemplateNotFound(template_str.clone())),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if options.no_input {
+            fill_defaults(&templates, &mut variables)?;
+        }
+
+        let env = project_env(project, path);
+        templates
+            .into_iter()
+            .map(|template| {
+                // Missing variables are prompted for, which the spinner would draw over
+                options.output.suspend(|| template.resolve_variables(&mut variables))?;
+                let mut rendered = template.render(&variables, config)?;
+                rendered.env = env.clone();
+                Ok(rendered)
+            })
+            .collect()
+    }
+
+    /// Validate the given value of each template variable, prompting for any that are missing
+    fn resolve_variables(&self, variables: &mut HashMap<String, String>) -> Result<()> {
+        if let Some(vars) = &self.template_vars {
+            for var in vars {
+                let spec = var.spec();
+                let value = match variables.get(&spec.name) {
+                    Some(value) => spec.validate(value)?,
+                    None => spec.prompt()?,
+                };
+                variables.insert(spec.name, value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render every file of the template and its layers, keyed by its path relative to the project directory
+    fn render(&self, variables: &HashMap<String, String>, config: &Config) -> Result<RenderedTemplate> {
+        // Each file along with the name of the template that wrote it
+        let mut written: BTreeMap<PathBuf, (String, String)> = BTreeMap::new();
+
+        for layer in self.layers.iter().chain(std::iter::once(self)) {
+            for (file, contents) in layer.render_files(variables, config)? {
+                if let Some((owner, _)) = written.get(&file) {
+                    match self.on_conflict {
+                        ConflictPolicy::Override => {},
+                        ConflictPolicy::Keep => continue,
+                        ConflictPolicy::Error => {
+                            return Err(Error::TemplateConflict(
+                                file.display().to_string(),
+                                owner.clone(),
+                                layer.name.clone(),
+                            ))
+                        },
+                    }
+                }
+                written.insert(file, (layer.name.clone(), contents));
+            }
+        }
+        let files = written.into_iter().map(|(file, (_, contents))| (file, contents)).collect();
+
+        let mut commands = Vec::new();
+        for command in self.commands.iter().flatten() {
+            if command.should_run(variables)? {
+                commands.push(command.clone());
+            }
+        }
+
+        Ok(RenderedTemplate {
+            name: self.name.clone(),
+            files,
+            commands,
+            env: BTreeMap::new(),
+        })
+    }
+
+    /// Render the files of this template alone, without its layers
+    fn render_files(&self, variables: &HashMap<String, String>, config: &Config) -> Result<Vec<(PathBuf, String)>> {
+        let root = template_root(config)?;
+        let mut files = Vec::new();
+
+        if let Some(template_dir) = &self.template_dir {
+            let template_dir = root.join(template_dir);
+            // Walk the template dir and load all files and their contents
+            files.extend(load_files(
+                &template_dir,
+                self.files_root(&template_dir),
+                variables,
+                self.engine,
+            )?);
+        }
+
+        if let Some(template_file) = &self.template_file {
+            let template_file = root.join(template_file);
+            let file_contents = load_file(&template_file, variables, self.engine)?;
+            let file_name = Path::new(template_file.file_name().unwrap());
+            files.push((render_path(file_name, variables, self.engine)?, file_contents));
+        }
+
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// Follow the `extends` and `includes` of a template, adding each template they lead to after its own parents
+fn collect_layers(
+    template: &Template,
+    templates: &HashMap<String, Template>,
+    stack: &mut Vec<String>,
+    layers: &mut Vec<Template>,
+) -> Result<()> {
+    for parent in template.extends.iter().chain(template.includes.iter().flatten()) {
+        if stack.contains(parent) {
+            stack.push(parent.clone());
+            return Err(Error::TemplateCycle(stack.join(" -> ")));
+        }
+        let parent_template = match templates.get(parent) {
+            Some(parent_template) => parent_template,
+            None => return Err(Error::TemplateNotFound(parent.clone())),
+        };
+
+        stack.push(parent.clone());
+        collect_layers(parent_template, templates, stack, layers)?;
+        stack.pop();
+
+        // A template reached through more than one path is only added once
+        if !layers.iter().any(|layer| layer.name == *parent) {
+            layers.push(parent_template.clone());
+        }
+    }
+
+    Ok(())
+}
+
+/// A template rendered in memory, ready to be written into a project
+#[derive(Debug)]
+pub struct RenderedTemplate {
+    pub name: String,
+    /// The contents of each file, keyed by its path relative to the project directory
+    pub files: Vec<(PathBuf, String)>,
+    pub commands: Vec<TemplateCommand>,
+    /// The environment the commands are run with
+    pub env: BTreeMap<String, String>,
+}
+
+impl RenderedTemplate {
+    /// Write the files into the project directory and run the commands there
+    pub fn write(&self, path: &Path, policy: OverwritePolicy, output: &CommandOutput) -> Result<BuildSummary> {
+        let summary = self.write_files(path, policy)?;
+
+        for command in &self.commands {
+            command.run(path, &self.env, output)?;
+        }
+
+        Ok(summary)
+    }
+
+    pub fn write_files(&self, path: &Path, policy: OverwritePolicy) -> Result<BuildSummary> {
+        write_files(path, &self.files, policy)
+    }
+}
+
+/// Write rendered files into the project directory, deciding what to do with existing files using the policy
+fn write_files(path: &Path, files: &[(PathBuf, String)], policy: OverwritePolicy) -> Result<BuildSummary> {
+    let mut summary = BuildSummary::default();
+
+    for (relative, contents) in files {
+        let relative = relative.clone();
+        let file = path.join(&relative);
+
+        if file.exists() {
+            if fs::read(&file).is_ok_and(|existing| existing == contents.as_bytes()) {
+                summary.unchanged.push(relative);
+                continue;
+            }
+
+            let overwrite = match policy {
+                OverwritePolicy::Overwrite => true,
+                OverwritePolicy::SkipExisting => false,
+                OverwritePolicy::Prompt => Confirm::with_theme(&ColorfulTheme::default())
+                    .with_prompt(format!("`{}` already exists, overwrite it?", relative.display()))
+                    .default(false)
+                    .interact()?,
+            };
+            if !overwrite {
+                summary.skipped.push(relative);
+                continue;
+            }
+
+            fs::write(file, contents)?;
+            summary.changed.push(relative);
+        } else {
+            fs::create_dir_all(file.parent().unwrap())?;
+            fs::write(file, contents)?;
+            summary.created.push(relative);
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Use the defaults for any variables without a value, failing with every variable that is still missing
+fn fill_defaults(templates: &[&Template], variables: &mut HashMap<String, String>) -> Result<()> {
+    let mut missing = Vec::new();
+
+    for var in templates.iter().flat_map(|template| template.template_vars.iter().flatten()) {
+        let spec = var.spec();
+        if variables.contains_key(&spec.name) || missing.contains(&spec.name) {
+            continue;
+        }
+        match spec.default {
+            Some(default) => {
+                variables.insert(spec.name, default);
+            },
+            None => missing.push(spec.name),
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::MissingTemplateVariables(missing))
+    }
+}
+
+/// The names of the variables set by `builtin_variables`
+pub const BUILTIN_VARIABLES: [&str; 11] = [
+    "project_name",
+    "project_desc",
+    "project_language",
+    "project_category",
+    "project_tags",
+    "project_dir",
+    "year",
+    "date",
+    "author_name",
+    "author_email",
+    "username",
+];
+
+/// Functions that Jinja templates can call, which look like variables to minijinja
+const JINJA_GLOBALS: [&str; 4] = ["range", "dict", "debug", "namespace"];
+
+fn template_root(config: &Config) -> Result<&Path> {
+    match &config.template_dir {
+        Some(dir) => Ok(dir),
+        None => Err(Error::ConfigMissingValue("template_dir".to_owned())),
+    }
+}
+
+/// Find the names of the variables used in a template file
+fn referenced_variables(contents: &str, engine: Engine) -> Result<HashSet<String>> {
+    match engine {
+        Engine::Legacy => {
+            let regex = Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap();
+            Ok(regex.captures_iter(contents).map(|captures| captures[1].to_owned()).collect())
+        },
+        Engine::Jinja => {
+            let env = jinja_environment();
+            let template = env.template_from_str(contents)?;
+            Ok(template
+                .undeclared_variables(false)
+                .into_iter()
+                .filter(|var| !JINJA_GLOBALS.contains(&var.as_str()))
+                .collect())
+        },
+    }
+}
+
+/// The fields of the project as `PO_PROJECT_*` environment variables for commands and hooks
+fn project_env(project: &Project, path: &Path) -> BTreeMap<String, String> {
+    let mut env = BTreeMap::from([
+        ("PO_PROJECT_NAME".to_owned(), project.name.clone().unwrap_or_default()),
+        ("PO_PROJECT_DESC".to_owned(), project.desc.clone().unwrap_or_default()),
+        ("PO_PROJECT_DIR".to_owned(), path.display().to_string()),
+        ("PO_PROJECT_LANGUAGE".to_owned(), project.language.clone().unwrap_or_default()),
+        ("PO_PROJECT_CATEGORY".to_owned(), project.category.clone().unwrap_or_default()),
+        (
+            "PO_PROJECT_TAGS".to_owned(),
+            project.tags.clone().unwrap_or_default().join(","),
+        ),
+        (
+            "PO_PROJECT_TEMPLATES".to_owned(),
+            project.templates.clone().unwrap_or_default().join(","),
+        ),
+    ]);
+    if let Some(rowid) = project.rowid {
+        env.insert("PO_PROJECT_ID".to_owned(), rowid.to_string());
+    }
+
+    env
+}
+
+/// Variables every template can use without declaring them
+fn builtin_variables(project: &Project, path: &Path) -> HashMap<String, String> {
+    let now = Local::now();
+    let mut variables = HashMap::from([
+        ("project_name".to_owned(), project.name.clone().unwrap_or_default()),
+        ("project_desc".to_owned(), project.desc.clone().unwrap_or_default()),
+        ("project_language".to_owned(), project.language.clone().unwrap_or_default()),
+        ("project_category".to_owned(), project.category.clone().unwrap_or_default()),
+        ("project_tags".to_owned(), project.tags.clone().unwrap_or_default().join(", ")),
+        ("project_dir".to_owned(), path.display().to_string()),
+        ("year".to_owned(), now.format("%Y").to_string()),
+        ("date".to_owned(), now.format("%Y-%m-%d").to_string()),
+    ]);
+
+    // Only set the values that can be found, so templates that need them still prompt
+    if let Some(name) = git_config("user.name") {
+        variables.insert("author_name".to_owned(), name);
+    }
+    if let Some(email) = git_config("user.email") {
+        variables.insert("author_email".to_owned(), email);
+    }
+    if let Ok(username) = std::env::var("USER").or_else(|_| std::env::var("USERNAME")) {
+        variables.insert("username".to_owned(), username);
+    }
+
+    variables
+}
+
+fn git_config(key: &str) -> Option<String> {
+    let output = Command::new("git").args(["config", "--get", key]).output().ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+    if output.status.success() && !value.is_empty() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+fn load_files(
+    dir: &Path,
+    root: &Path,
+    variables: &HashMap<String, String>,
+    engine: Engine,
+) -> Result<HashMap<PathBuf, String>> {
+    let mut files = HashMap::new();
+
+    for path in list_files(dir)? {
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        let file = render_path(relative, variables, engine)?;
+        let contents = load_file(&path, variables, engine)?;
+        files.insert(file, contents);
+    }
+
+    Ok(files)
+}
+
+/// Recursively list every file in a directory
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(list_files(&path)?);
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Render the variables in a relative path, making sure the result stays inside the project
+fn render_path(path: &Path, variables: &HashMap<String, String>, engine: Engine) -> Result<PathBuf> {
+    let rendered = PathBuf::from(render(&path.to_string_lossy(), variables, engine)?);
+
+    let escapes = rendered
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+    if escapes || rendered.as_os_str().is_empty() {
+        return Err(Error::TemplatePathTraversal(rendered.display().to_string()));
+    }
+
+    Ok(rendered)
+}
+
+fn load_file(path: &Path, variables: &HashMap<String, String>, engine: Engine) -> Result<String> {
+    let contents = fs::read_to_string(path)?;
+    render(&contents, variables, engine)
+}
+
+fn render(contents: &str, variables: &HashMap<String, String>, engine: Engine) -> Result<String> {
+    match engine {
+        Engine::Legacy => replace_variables(contents, variables),
+        Engine::Jinja => Ok(jinja_environment().render_str(contents, variables)?),
+    }
+}
+
+fn jinja_environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_keep_trailing_newline(true);
+
+    env.add_filter("snake_case", |value: String| value.to_snake_case());
+    env.add_filter("kebab_case", |value: String| value.to_kebab_case());
+    env.add_filter("camel_case", |value: String| value.to_lower_camel_case());
+    env.add_filter("PascalCase", |value: String| value.to_upper_camel_case());
+    env.add_filter("SCREAMING_SNAKE_CASE", |value: String| value.to_shouty_snake_case());
+    env.add_filter("title_case", |value: String| value.to_title_case());
+    // Variables are always strings, so lists are passed as separated values
+    env.add_filter("split", |value: String, sep: Option<String>| {
+        let sep = sep.unwrap_or_else(|| ",".to_owned());
+        value
+            .split(sep.as_str())
+            .map(|item| item.trim().to_owned())
+            .filter(|item| !item.is_empty())
+            .collect::<Vec<_>>()
+    });
+
+    env
+}
+
+fn replace_variables(contents: &str, variables: &HashMap<String, String>) -> Result<String> {
+    let mut contents = contents.to_string();
+
+    for (key, value) in variables {
+        contents = contents.replace(&format!("${{{}}}", key), value);
+    }
+
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use anyhow::Result;
+
+    #[test]
+    fn test_load_templates() {
+        let config = Config {
+            template_dir: Some(PathBuf::from("tests/templates")),
+            ..Default::default()
+        };
+
+        let expected_template = Template {
+            name: "test".to_owned(),
+            desc: Some("A Test Tempalte".to_owned()),
+            template_dir: Some(PathBuf::from("template_dir")),
+            template_file: Some(PathBuf::from("file_3.txt")),
+            template_vars: Some(vec![
+                TemplateVar::Name("number".to_owned()),
+                TemplateVar::Name("number2".to_owned()),
+            ]),
+            commands: Some(vec![TemplateCommand::Line("git init".to_owned())]),
+            ..Default::default()
+        };
+
+        let tempaltes = Template::load_templates(&config).unwrap();
+        let template = tempaltes.get("test").unwrap();
+
+        assert_eq!(*template, expected_template);
+    }
+
+    #[test]
+    fn test_load_file() {
+        let mut variables = HashMap::new();
+        variables.insert("number".to_owned(), "1".to_owned());
+        variables.insert("number2".to_owned(), "2".to_owned());
+        let file = load_file(
+            Path::new("tests/templates/template_dir/file_1.txt"),
+            &variables,
+            Engine::Legacy,
+        )
+        .unwrap();
+
+        let expected_file = Path::new("tests/templates/template_dir_expected/file_1.txt");
+        let expected_file = fs::read_to_string(expected_file).unwrap();
+        assert_eq!(file, expected_file);
+    }
+
+    #[test]
+    fn test_load_file_2() {
+        let mut variables = HashMap::new();
+        variables.insert("number".to_owned(), "1".to_owned());
+        variables.insert("number2".to_owned(), "2".to_owned());
+        let file = load_file(
+            Path::new("tests/templates/template_dir/file_2.txt"),
+            &variables,
+            Engine::Legacy,
+        )
+        .unwrap();
+
+        let expected_file = Path::new("tests/templates/template_dir_expected/file_2.txt");
+        let expected_file = fs::read_to_string(expected_file).unwrap();
+        assert_eq!(file, expected_file);
+    }
+
+    #[test]
+    fn test_load_files() {
+        let mut variables = HashMap::new();
+        variables.insert("number".to_owned(), "1".to_owned());
+        variables.insert("number2".to_owned(), "2".to_owned());
+        let files = load_files(
+            Path::new("tests/templates/template_dir"),
+            Path::new("tests/templates"),
+            &variables,
+            Engine::Legacy,
+        )
+        .unwrap();
+
+        let expected_file_1 = Path::new("tests/templates/template_dir_expected/file_1.txt");
+        let expected_file_1 = fs::read_to_string(expected_file_1).unwrap();
+        let expected_file_2 = Path::new("tests/templates/template_dir_expected/file_2.txt");
+        let expected_file_2 = fs::read_to_string(expected_file_2).unwrap();
+
+        assert_eq!(*files.get(Path::new("template_dir/file_1.txt")).unwrap(), expected_file_1);
+        assert_eq!(*files.get(Path::new("template_dir/file_2.txt")).unwrap(), expected_file_2);
+        assert_eq!(*files.get(Path::new("template_dir/1/file_2.txt")).unwrap(), "Nested file 2\n");
+    }
+
+    #[test]
+    fn test_validate() {
+        let config = Config {
+            template_dir: Some(PathBuf::from("tests/templates")),
+            ..Default::default()
+        };
+        let templates = Template::load_templates(&config).unwrap();
+        assert_eq!(templates["test"].validate(&config).unwrap(), Vec::<String>::new());
+
+        let template: Template = serde_yaml::from_str(
+            "
+name: broken
+template_dir: template_dir
+template_file: missing.txt
+",
+        )
+        .unwrap();
+        assert_eq!(
+            template.validate(&config).unwrap(),
+            vec!["the template file `missing.txt` does not exist"]
+        );
+
+        let template: Template = serde_yaml::from_str(
+            "
+name: broken
+template_dir: template_dir
+template_vars:
+  - number
+  - unused
+",
+        )
+        .unwrap();
+        assert_eq!(
+            template.validate(&config).unwrap(),
+            vec![
+                "the variable `unused` is declared but never used",
+                "the variable `number2` is used but never declared",
+            ]
+        );
+
+        // Variables in a command condition count as used, and have to be declared like any other
+        let template: Template = serde_yaml::from_str(
+            r#"
+name: conditional
+template_dir: template_dir
+template_vars: [number, number2, kind]
+commands:
+  - cmd: cargo
+    args: [init, --bin]
+    when: kind == "bin" and not ci
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            template.validate(&config).unwrap(),
+            vec!["the variable `ci` is used but never declared"]
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let templates = [
+            "
+name: base
+desc: The base
+template_file: file_3.txt
+template_vars:
+  - name: license
+    default: MIT
+commands:
+  - git init
+",
+            "
+name: ci
+template_vars: [number]
+commands: [echo ci]
+",
+            "
+name: child
+extends: base
+includes: [ci, base]
+template_vars:
+  - name: license
+    default: Apache-2.0
+commands: [echo child]
+",
+            "
+name: loop_a
+includes: [loop_b]
+",
+            "
+name: loop_b
+extends: loop_a
+",
+        ]
+        .into_iter()
+        .map(|yaml| serde_yaml::from_str::<Template>(yaml).unwrap())
+        .map(|template| (template.name.clone(), template))
+        .collect::<HashMap<_, _>>();
+
+        let child = Template::resolve("child", &templates).unwrap();
+        assert_eq!(child.desc.as_deref(), Some("The base"));
+        assert_eq!(
+            child.layers.iter().map(|layer| layer.name.as_str()).collect::<Vec<_>>(),
+            vec!["base", "ci"]
+        );
+        let vars = child
+            .template_vars
+            .iter()
+            .flatten()
+            .map(TemplateVar::spec)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vars.iter().map(|var| var.name.as_str()).collect::<Vec<_>>(),
+            vec!["license", "number"]
+        );
+        assert_eq!(vars[0].default.as_deref(), Some("Apache-2.0"));
+        assert_eq!(
+            child.commands.iter().flatten().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["git init", "echo ci", "echo child"]
+        );
+
+        match Template::resolve("loop_a", &templates) {
+            Err(Error::TemplateCycle(cycle)) => assert_eq!(cycle, "loop_a -> loop_b -> loop_a"),
+            result => panic!("expected a cycle, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_render_conflicts() {
+        let config = Config {
+            template_dir: Some(PathBuf::from("tests/templates")),
+            ..Default::default()
+        };
+        let templates = [
+            "
+name: first
+template_file: file_3.txt
+",
+            "
+name: second
+template_dir: template_dir
+template_file: template_dir/file_1.txt
+",
+            "
+name: both
+includes: [first, second]
+on_conflict: error
+",
+        ]
+        .into_iter()
+        .map(|yaml| serde_yaml::from_str::<Template>(yaml).unwrap())
+        .map(|template| (template.name.clone(), template))
+        .collect::<HashMap<_, _>>();
+        let variables = HashMap::from([("number".to_owned(), "1".to_owned()), ("number2".to_owned(), "2".to_owned())]);
+
+        let mut both = Template::resolve("both", &templates).unwrap();
+        let rendered = both.render(&variables, &config).unwrap();
+        assert_eq!(rendered.files.len(), 5);
+
+        // The same file rendered by another engine, so the two versions differ
+        both.template_file = Some(PathBuf::from("template_dir/file_1.txt"));
+        both.engine = Engine::Jinja;
+        match both.render(&variables, &config) {
+            Err(Error::TemplateConflict(file, first, second)) => {
+                assert_eq!(
+                    (file.as_str(), first.as_str(), second.as_str()),
+                    ("file_1.txt", "second", "both")
+                )
+            },
+            result => panic!("expected a conflict, got {result:?}"),
+        }
+
+        let file_1 = |rendered: RenderedTemplate| {
+            rendered
+                .files
+                .into_iter()
+                .find(|(file, _)| file == Path::new("file_1.txt"))
+                .map(|(_, contents)| contents)
+                .unwrap()
+        };
+        both.on_conflict = ConflictPolicy::Keep;
+        assert!(file_1(both.render(&variables, &config).unwrap()).contains("number: 1"));
+        both.on_conflict = ConflictPolicy::Override;
+        assert!(file_1(both.render(&variables, &config).unwrap()).contains("number: ${number}"));
+
+        // The source files follow the same policy, so each path is listed once
+        let source = |template: &Template| {
+            template
+                .source_files(&config)
+                .unwrap()
+                .into_iter()
+                .find(|(_, file)| file == Path::new("file_1.txt"))
+                .map(|(source, _)| source)
+                .unwrap()
+        };
+        assert_eq!(both.source_files(&config).unwrap().len(), 5);
+        assert_eq!(source(&both), PathBuf::from("tests/templates/template_dir/file_1.txt"));
+        both.on_conflict = ConflictPolicy::Error;
+        assert!(matches!(both.source_files(&config), Err(Error::TemplateConflict(..))));
+    }
+
+    #[test]
+    fn test_load_resolved() -> Result<()> {
+        let test_dir = Path::new("tests/test_files/load_resolved");
+        if test_dir.exists() {
+            fs::remove_dir_all(test_dir)?;
+        }
+        fs::create_dir_all(test_dir)?;
+        fs::write(test_dir.join("good.yaml"), "name: good\n")?;
+        fs::write(test_dir.join("broken.yaml"), "name: broken\nextends: missing\n")?;
+        fs::write(test_dir.join("good_copy.yaml"), "name: good\ndesc: The copy\n")?;
+        let config = Config {
+            template_dir: Some(test_dir.to_path_buf()),
+            ..Default::default()
+        };
+
+        // A template that can't be resolved only fails when it's asked for
+        // A second template with the same name is left out rather than failing
+        let templates = Template::load_resolved(&config, &["good".to_owned()])?;
+        assert_eq!(templates.keys().collect::<Vec<_>>(), vec!["good"]);
+        assert_eq!(templates["good"].desc, None);
+        assert!(matches!(
+            Template::load_resolved(&config, &["broken".to_owned()]),
+            Err(Error::TemplateNotFound(name)) if name == "missing"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_referenced_variables() {
+        let vars = referenced_variables("${name} {{ other }} ${year}", Engine::Legacy).unwrap();
+        assert_eq!(vars, HashSet::from(["name".to_owned(), "year".to_owned()]));
+
+        let contents = "{% for x in range(3) %}{{ x }}{{ name | upper }}{% endfor %}{% if kind %}{% endif %}";
+        let vars = referenced_variables(contents, Engine::Jinja).unwrap();
+        assert_eq!(vars, HashSet::from(["name".to_owned(), "kind".to_owned()]));
+    }
+
+    #[test]
+    fn test_fill_defaults() {
+        let template: Template = serde_yaml::from_str(
+            "
+name: defaults
+template_vars:
+  - given
+  - name: defaulted
+    default: 3
+  - missing
+  - name: also_missing
+",
+        )
+        .unwrap();
+
+        let mut variables = HashMap::from([("given".to_owned(), "1".to_owned())]);
+        let result = fill_defaults(&[&template], &mut variables);
+
+        assert_eq!(variables["defaulted"], "3");
+        match result {
+            Err(Error::MissingTemplateVariables(missing)) => assert_eq!(missing, vec!["missing", "also_missing"]),
+            result => panic!("expected missing variables, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_builtin_variables() {
+        let project = Project::new(
+            Some("Test".to_owned()),
+            None,
+            vec!["cli".to_owned(), "tool".to_owned()],
+            Some("rust".to_owned()),
+            None,
+        );
+        let variables = builtin_variables(&project, Path::new("/dev/test"));
+
+        assert_eq!(variables["project_name"], "Test");
+        assert_eq!(variables["project_desc"], "");
+        assert_eq!(variables["project_language"], "rust");
+        assert_eq!(variables["project_tags"], "cli, tool");
+        assert_eq!(variables["project_dir"], Path::new("/dev/test").display().to_string());
+        assert_eq!(variables["year"].len(), 4);
+        assert_eq!(variables["date"].len(), 10);
+    }
+
+    #[test]
+    fn test_render_path() {
+        let mut variables = HashMap::new();
+        variables.insert("crate_name".to_owned(), "my_crate".to_owned());
+        variables.insert("up".to_owned(), "..".to_owned());
+        variables.insert("root".to_owned(), "/etc".to_owned());
+
+        assert_eq!(
+            render_path(Path::new("src/${crate_name}/mod.rs"), &variables, Engine::Legacy).unwrap(),
+            PathBuf::from("src/my_crate/mod.rs")
+        );
+        assert_eq!(
+            render_path(Path::new("{{ crate_name | PascalCase }}.sln"), &variables, Engine::Jinja).unwrap(),
+            PathBuf::from("MyCrate.sln")
+        );
+        assert!(matches!(
+            render_path(Path::new("src/${up}/passwd"), &variables, Engine::Legacy),
+            Err(Error::TemplatePathTraversal(_))
+        ));
+        assert!(matches!(
+            render_path(Path::new("${root}/passwd"), &variables, Engine::Legacy),
+            Err(Error::TemplatePathTraversal(_))
+        ));
+    }
+
+    #[test]
+    fn test_render_jinja() {
+        let mut variables = HashMap::new();
+        variables.insert("name".to_owned(), "my cool project".to_owned());
+        variables.insert("kind".to_owned(), "bin".to_owned());
+        variables.insert("features".to_owned(), "serde, clap".to_owned());
+
+        let contents = "{{ name | snake_case }} {{ name | kebab_case }} {{ name | PascalCase }} {{ name | upper }}
+{% if kind == \"bin\" %}fn main() {}{% else %}pub fn lib() {}{% endif %}
+{% for feature in features | split %}- {{ feature }}
+{% endfor %}";
+        let expected = "my_cool_project my-cool-project MyCoolProject MY COOL PROJECT
+fn main() {}
+- serde
+- clap
+";
+
+        assert_eq!(render(contents, &variables, Engine::Jinja).unwrap(), expected);
+        assert_eq!(
+            render("${name} {{ name }}", &variables, Engine::Legacy).unwrap(),
+            "my cool project {{ name }}"
+        );
+    }
+
+    #[test]
+    fn test_build_tempate() -> Result<()> {
+        let config = Config {
+            template_dir: Some(PathBuf::from("tests/templates")),
+
+            ..Default::default()
+        };
+        let mut variables = HashMap::new();
+        variables.insert("number".to_owned(), "1".to_owned());
+        variables.insert("number2".to_owned(), "2".to_owned());
+        let tempaltes = Template::load_templates(&config).unwrap();
+        let template = tempaltes.get("test").unwrap();
+
+        // Delete test directory if it exists
+        let test_dir = Path::new("tests/test_files/template_test");
+        if test_dir.exists() {
+            fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        template.resolve_variables(&mut variables)?;
+        let summary =
+            template
+                .render(&variables, &config)?
+                .write(test_dir, OverwritePolicy::Overwrite, &CommandOutput::default())?;
+        assert_eq!(summary.created.len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_files() -> Result<()> {
+        let test_dir = Path::new("tests/test_files/write_files");
+        if test_dir.exists() {
+            fs::remove_dir_all(test_dir)?;
+        }
+        let files = vec![
+            (PathBuf::from("same.txt"), "same".to_owned()),
+            (PathBuf::from("dir/edited.txt"), "template".to_owned()),
+        ];
+
+        let summary = write_files(test_dir, &files, OverwritePolicy::Overwrite)?;
+        assert_eq!(
+            summary.created,
+            vec![PathBuf::from("same.txt"), PathBuf::from("dir/edited.txt")]
+        );
+
+        fs::write(test_dir.join("dir/edited.txt"), "edited")?;
+        let summary = write_files(test_dir, &files, OverwritePolicy::SkipExisting)?;
+        assert_eq!(summary.unchanged, vec![PathBuf::from("same.txt")]);
+        assert_eq!(summary.skipped, vec![PathBuf::from("dir/edited.txt")]);
+        assert_eq!(fs::read_to_string(test_dir.join("dir/edited.txt"))?, "edited");
+
+        let summary = write_files(test_dir, &files, OverwritePolicy::Overwrite)?;
+        assert_eq!(summary.changed, vec![PathBuf::from("dir/edited.txt")]);
+        assert_eq!(fs::read_to_string(test_dir.join("dir/edited.txt"))?, "template");
+
+        Ok(())
+    }
+}
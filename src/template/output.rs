@@ -0,0 +1,172 @@
+use chrono::Local;
+use console::style;
+use fs_err as fs;
+use indicatif::ProgressBar;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::{Duration, Instant};
+
+/// How much of the template commands is shown while they run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Only errors
+    Quiet,
+    /// A line for each command along with its output
+    #[default]
+    Normal,
+    /// Also the directory and environment of each command and how it exited
+    Verbose,
+}
+
+/// Where the output of template commands goes
+#[derive(Debug, Default, Clone)]
+pub struct CommandOutput {
+    pub verbosity: Verbosity,
+    /// The spinner to print above, so it keeps ticking below the output
+    pub progress: Option<ProgressBar>,
+    /// Everything every command prints is appended here
+    pub log_file: Option<PathBuf>,
+}
+
+/// Which stream of a command a line was printed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// The output of one running command
+pub struct CommandRun<'a> {
+    output: &'a CommandOutput,
+    command: String,
+    log: Option<fs::File>,
+    start: Instant,
+}
+
+impl CommandOutput {
+    pub fn begin(&self, command: &str, cwd: &Path, env: &[(&String, &String)]) -> CommandRun<'_> {
+        if let Some(pb) = &self.progress {
+            pb.set_message(format!("Running `{command}`..."));
+        }
+        if self.verbosity == Verbosity::Verbose {
+            self.print(&format!("{} {command}", style("$").bold()));
+            self.print(&style(format!("  in {}", cwd.display())).dim().to_string());
+            for (key, value) in env {
+                self.print(&style(format!("  with {key}={value}")).dim().to_string());
+            }
+        }
+
+        let mut log = self.open_log();
+        if let Some(log) = &mut log {
+            let _ = writeln!(log, "[{}] $ {command}", timestamp());
+            let _ = writeln!(log, "cwd: {}", cwd.display());
+        }
+
+        CommandRun {
+            output: self,
+            command: command.to_owned(),
+            log,
+            start: Instant::now(),
+        }
+    }
+
+    /// Hide the spinner while `f` runs, so it can ask the user for input
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        match &self.progress {
+            Some(pb) => pb.suspend(f),
+            None => f(),
+        }
+    }
+
+    /// Note that a command was not run
+    pub fn skip(&self, command: &str, reason: &str) {
+        if self.verbosity != Verbosity::Quiet {
+            self.print(&format!("{} {command} (skipped, {reason})", style("-").dim()));
+        }
+        self.log(&format!("[{}] skipped `{command}`, {reason}\n", timestamp()));
+    }
+
+    /// Print something about how a command is being handled, such as a retry
+    pub fn notice(&self, message: &str) {
+        if self.verbosity != Verbosity::Quiet {
+            self.print(&format!("{} {message}", style("!").yellow()));
+        }
+        self.log(&format!("{message}\n"));
+    }
+
+    fn log(&self, line: &str) {
+        if let Some(mut log) = self.open_log() {
+            let _ = writeln!(log, "{line}");
+        }
+    }
+
+    // A log that can't be written to shouldn't stop the project from being built
+    fn open_log(&self) -> Option<fs::File> {
+        let path = self.log_file.as_ref()?;
+        fs::OpenOptions::new().create(true).append(true).open(path).ok()
+    }
+
+    /// Print a line above the spinner, or to stderr when there isn't one showing
+    fn print(&self, line: &str) {
+        match &self.progress {
+            Some(pb) if !pb.is_hidden() => pb.println(line),
+            _ => eprintln!("{line}"),
+        }
+    }
+}
+
+impl CommandRun<'_> {
+    pub fn line(&mut self, stream: Stream, line: &str) {
+        if self.output.verbosity != Verbosity::Quiet {
+            match stream {
+                Stream::Stdout => self.output.print(&format!("  {line}")),
+                Stream::Stderr => self.output.print(&style(format!("  {line}")).dim().to_string()),
+            }
+        }
+        if let Some(log) = &mut self.log {
+            let prefix = match stream {
+                Stream::Stdout => "out",
+                Stream::Stderr => "err",
+            };
+            let _ = writeln!(log, "{prefix} | {line}");
+        }
+    }
+
+    /// Record how the command ended, `None` when it never exited on its own
+    pub fn finish(mut self, status: Option<ExitStatus>) {
+        let elapsed = format_duration(self.start.elapsed());
+        let success = status.is_some_and(|status| status.success());
+
+        if let Some(log) = &mut self.log {
+            let status = match status {
+                Some(status) => status.to_string(),
+                None => "stopped without exiting".to_owned(),
+            };
+            let _ = writeln!(log, "{status} after {elapsed}\n");
+        }
+
+        match self.output.verbosity {
+            Verbosity::Quiet => {},
+            _ if success => self
+                .output
+                .print(&format!("{} {} ({elapsed})", style("✔").green(), self.command)),
+            _ => self
+                .output
+                .print(&format!("{} {} ({elapsed})", style("✘").red(), self.command)),
+        }
+        if self.output.verbosity == Verbosity::Verbose {
+            if let Some(status) = status {
+                self.output.print(&style(format!("  {status}")).dim().to_string());
+            }
+        }
+    }
+}
+
+fn timestamp() -> String {
+    Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1}s", duration.as_secs_f64())
+}
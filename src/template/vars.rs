@@ -0,0 +1,270 @@
+use crate::{
+    error::Error,
+    utils::{Format, Result},
+};
+use console::style;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Input, Select};
+use fs_err as fs;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A variable declared by a template, either just its name or a full description of it
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum TemplateVar {
+    Name(String),
+    Spec(VarSpec),
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct VarSpec {
+    pub name: String,
+    /// Text shown when asking for the value, defaults to the name of the variable
+    pub prompt: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_scalar")]
+    pub default: Option<String>,
+    #[serde(rename = "type", default)]
+    pub kind: VarType,
+    /// The allowed values of a `choice` variable
+    pub choices: Option<Vec<String>>,
+    /// A pattern the whole value has to match
+    pub regex: Option<String>,
+    pub help: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum VarType {
+    #[default]
+    String,
+    Bool,
+    Int,
+    Choice,
+}
+
+impl TemplateVar {
+    pub fn spec(&self) -> VarSpec {
+        match self {
+            TemplateVar::Name(name) => VarSpec {
+                name: name.clone(),
+                ..Default::default()
+            },
+            TemplateVar::Spec(spec) => spec.clone(),
+        }
+    }
+}
+
+impl VarSpec {
+    /// Ask the user for the value of the variable
+    pub fn prompt(&self) -> Result<String> {
+        let theme = ColorfulTheme::default();
+        let prompt = self
+            .prompt
+            .clone()
+            .unwrap_or_else(|| format!("Enter the value for {}", self.name));
+
+        if let Some(help) = &self.help {
+            println!("{}", style(help).dim());
+        }
+
+        let value = match self.kind {
+            VarType::Bool => {
+                // Checking the default reports a bad one against the name of the variable
+                let default = self.default.as_deref().map(|default| self.validate(default)).transpose()?;
+                let default = default.is_some_and(|default| default == "true");
+                Confirm::with_theme(&theme)
+                    .with_prompt(prompt)
+                    .default(default)
+                    .interact()?
+                    .to_string()
+            },
+            VarType::Choice => {
+                let choices = self.choices.clone().unwrap_or_default();
+                let default = self
+                    .default
+                    .as_ref()
+                    .and_then(|default| choices.iter().position(|choice| choice == default))
+                    .unwrap_or_default();
+                let selection = Select::with_theme(&theme)
+                    .with_prompt(prompt)
+                    .items(&choices)
+                    .default(default)
+                    .interact()?;
+                choices[selection].clone()
+            },
+            VarType::String | VarType::Int => {
+                let mut input = Input::<String>::with_theme(&theme);
+                input.with_prompt(prompt);
+                if let Some(default) = &self.default {
+                    input.default(default.clone());
+                }
+                input
+                    .validate_with(|value: &String| -> core::result::Result<(), String> {
+                        self.validate(value).map(|_| ()).map_err(|e| e.to_string())
+                    })
+                    .interact_text()?
+            },
+        };
+
+        self.validate(&value)
+    }
+
+    /// Check that a value is allowed for the variable, returning the normalized value
+    pub fn validate(&self, value: &str) -> Result<String> {
+        let invalid = |reason: String| Error::InvalidTemplateVariable(self.name.clone(), reason);
+
+        let value = match self.kind {
+            VarType::String => value.to_owned(),
+            VarType::Bool => parse_bool(value)
+                .ok_or_else(|| invalid(format!("`{value}` is not true or false")))?
+                .to_string(),
+            VarType::Int => value
+                .trim()
+                .parse::<i64>()
+                .map_err(|_| invalid(format!("`{value}` is not a whole number")))?
+                .to_string(),
+            VarType::Choice => {
+                let choices = self.choices.clone().unwrap_or_default();
+                if !choices.iter().any(|choice| choice == value) {
+                    return Err(invalid(format!("`{value}` is not one of {}", choices.join(", "))));
+                }
+                value.to_owned()
+            },
+        };
+
+        if let Some(pattern) = &self.regex {
+            let regex = Regex::new(&format!("^(?:{pattern})$"))
+                .map_err(|e| invalid(format!("the regex `{pattern}` is invalid: {e}")))?;
+            if !regex.is_match(&value) {
+                return Err(invalid(format!("`{value}` does not match `{pattern}`")));
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" => Some(true),
+        "false" | "no" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Values can be written as any scalar, but variables are always strings
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Scalar {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl From<Scalar> for String {
+    fn from(scalar: Scalar) -> Self {
+        match scalar {
+            Scalar::Bool(value) => value.to_string(),
+            Scalar::Int(value) => value.to_string(),
+            Scalar::Float(value) => value.to_string(),
+            Scalar::String(value) => value,
+        }
+    }
+}
+
+fn deserialize_scalar<'de, D: Deserializer<'de>>(deserializer: D) -> core::result::Result<Option<String>, D::Error> {
+    Ok(Option::<Scalar>::deserialize(deserializer)?.map(String::from))
+}
+
+/// Load the values of template variables from a YAML, JSON, or TOML file of `name: value` pairs
+pub fn load_vars_file(path: &Path) -> Result<HashMap<String, String>> {
+    let contents = fs::read_to_string(path)?;
+
+    let vars: HashMap<String, Scalar> = match Format::from_path(path)? {
+        Format::Json => serde_json::from_str(&contents)?,
+        Format::Yaml => serde_yaml::from_str(&contents)?,
+        Format::Toml => toml::from_str(&contents)?,
+    };
+
+    Ok(vars.into_iter().map(|(name, value)| (name, value.into())).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vars() {
+        let vars: Vec<TemplateVar> = serde_yaml::from_str(
+            "
+- plain
+- name: kind
+  type: choice
+  choices: [bin, lib]
+  default: bin
+- name: docker
+  type: bool
+  default: true
+",
+        )
+        .unwrap();
+
+        assert_eq!(vars[0], TemplateVar::Name("plain".to_owned()));
+        assert_eq!(vars[1].spec().kind, VarType::Choice);
+        assert_eq!(vars[2].spec().name, "docker");
+        assert_eq!(vars[2].spec().default, Some("true".to_owned()));
+    }
+
+    #[test]
+    fn test_load_vars_file() {
+        let path = Path::new("tests/test_files/vars.toml");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "name = \"app\"\nport = 8080\ndocker = true\n").unwrap();
+
+        let vars = load_vars_file(path).unwrap();
+        assert_eq!(vars["name"], "app");
+        assert_eq!(vars["port"], "8080");
+        assert_eq!(vars["docker"], "true");
+    }
+
+    #[test]
+    fn test_validate() {
+        let int = VarSpec {
+            name: "port".to_owned(),
+            kind: VarType::Int,
+            ..Default::default()
+        };
+        assert_eq!(int.validate(" 8080").unwrap(), "8080");
+        assert!(int.validate("eighty").is_err());
+
+        let bool = VarSpec {
+            name: "use_ci".to_owned(),
+            kind: VarType::Bool,
+            ..Default::default()
+        };
+        assert_eq!(bool.validate("yes").unwrap(), "true");
+        match bool.validate("maybe") {
+            Err(Error::InvalidTemplateVariable(name, _)) => assert_eq!(name, "use_ci"),
+            result => panic!("expected an invalid value, got {result:?}"),
+        }
+
+        let choice = VarSpec {
+            kind: VarType::Choice,
+            choices: Some(vec!["bin".to_owned(), "lib".to_owned()]),
+            ..Default::default()
+        };
+        assert!(choice.validate("lib").is_ok());
+        assert!(choice.validate("proc-macro").is_err());
+
+        let regex = VarSpec {
+            regex: Some("[a-z_]+".to_owned()),
+            ..Default::default()
+        };
+        assert!(regex.validate("crate_name").is_ok());
+        assert!(regex.validate("Crate-Name").is_err());
+    }
+}
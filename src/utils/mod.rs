@@ -1,17 +1,112 @@
 use crate::error::Error;
 use dirs::config_dir;
+use fs_err as fs;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 pub type Result<T> = core::result::Result<T, Error>;
 
+/// The file formats that projects and variables can be read from and written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self> {
+        path.extension()
+            .and_then(|ext| Format::from_name(&ext.to_string_lossy()))
+            .ok_or_else(|| Error::UnknownFormat(path.display().to_string()))
+    }
+}
+
 pub fn config_folder() -> PathBuf {
     let mut config_folder = config_dir().unwrap_or_default();
     config_folder.push("ProjectOrganizer");
     config_folder
 }
 
+pub fn trash_folder() -> PathBuf {
+    let mut trash_folder = config_folder();
+    trash_folder.push("trash");
+    trash_folder
+}
+
+/// Move a directory into the trash folder, returning where it ended up
+pub fn move_to_trash(dir: &Path) -> Result<PathBuf> {
+    let trash = trash_folder();
+    fs::create_dir_all(&trash)?;
+
+    let name = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let mut dest = trash.join(&name);
+    let mut count = 1;
+    while dest.exists() {
+        dest = trash.join(format!("{name}-{count}"));
+        count += 1;
+    }
+
+    // Renaming fails when the trash is on a different filesystem, so fall back to copying
+    if fs::rename(dir, &dest).is_err() {
+        copy_dir_all(dir, &dest)?;
+        fs::remove_dir_all(dir)?;
+    }
+
+    Ok(dest)
+}
+
+pub fn copy_dir_all(src: &Path, dest: &Path) -> Result<()> {
+    fs::create_dir_all(dest)?;
+
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            copy_dir_all(&path, &dest.join(entry.file_name()))?;
+        } else {
+            fs::copy(&path, dest.join(entry.file_name()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Score how well `query` fuzzy matches `candidate`, or `None` if it doesn't match at all.
+/// Every character of the query has to appear in order, and consecutive matches score higher.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query = query.to_lowercase();
+    let candidate = candidate.to_lowercase();
+
+    if candidate.contains(&query) {
+        return Some(1000 - (candidate.len() as i64 - query.len() as i64));
+    }
+
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    let mut chars = candidate.char_indices();
+    for q in query.chars() {
+        let (index, _) = chars.find(|(_, c)| *c == q)?;
+        score += match last_match {
+            Some(last) if last + 1 == index => 10,
+            _ => 1,
+        };
+        last_match = Some(index);
+    }
+
+    Some(score)
+}
+
 pub fn create_spinner(msg: &str) -> Result<ProgressBar> {
     let pb = ProgressBar::new_spinner();
     pb.enable_steady_tick(Duration::from_millis(80));
@@ -23,3 +118,28 @@ pub fn create_spinner(msg: &str) -> Result<ProgressBar> {
     pb.set_message(msg.to_owned());
     Ok(pb)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path(Path::new("projects.yml")).unwrap(), Format::Yaml);
+        assert_eq!(Format::from_path(Path::new("projects.TOML")).unwrap(), Format::Toml);
+        assert!(Format::from_path(Path::new("projects")).is_err());
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("xyz", "project"), None);
+        assert_eq!(fuzzy_score("jce", "project"), None);
+        assert!(fuzzy_score("proj", "project").is_some());
+        assert!(fuzzy_score("pjt", "project").is_some());
+
+        // Substrings beat scattered matches, and shorter candidates beat longer ones
+        assert!(fuzzy_score("proj", "project") > fuzzy_score("prt", "project"));
+        assert!(fuzzy_score("proj", "project") > fuzzy_score("proj", "project_organizer"));
+        assert!(fuzzy_score("PROJ", "project").is_some());
+    }
+}